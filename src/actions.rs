@@ -0,0 +1,438 @@
+use bevy::{input::InputSystem, prelude::*, utils::HashSet};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+const STICK_THRESHOLD: f32 = 0.5;
+
+pub struct ActionsPlugin;
+
+impl Plugin for ActionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InputMap>()
+            .init_resource::<ActionState>()
+            .add_systems(PreUpdate, update_action_state_system.after(InputSystem));
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Action {
+    MoveLeft,
+    MoveRight,
+    Jump,
+    Interact,
+    Confirm,
+    Cancel,
+    DeleteChar,
+    NextChar,
+    PreviousChar,
+    NextSlot,
+    PreviousSlot,
+    FirstSlot,
+    LastSlot,
+    DeleteNextChar,
+}
+
+impl Action {
+    pub const ALL: [Action; 14] = [
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::Jump,
+        Action::Interact,
+        Action::Confirm,
+        Action::Cancel,
+        Action::DeleteChar,
+        Action::NextChar,
+        Action::PreviousChar,
+        Action::NextSlot,
+        Action::PreviousSlot,
+        Action::FirstSlot,
+        Action::LastSlot,
+        Action::DeleteNextChar,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Action::MoveLeft => "Move left",
+            Action::MoveRight => "Move right",
+            Action::Jump => "Jump",
+            Action::Interact => "Interact",
+            Action::Confirm => "Confirm",
+            Action::Cancel => "Cancel",
+            Action::DeleteChar => "Delete character",
+            Action::NextChar => "Next character",
+            Action::PreviousChar => "Previous character",
+            Action::NextSlot => "Next slot",
+            Action::PreviousSlot => "Previous slot",
+            Action::FirstSlot => "First slot",
+            Action::LastSlot => "Last slot",
+            Action::DeleteNextChar => "Delete next character",
+        }
+    }
+
+    /// Walking around and entering an answer never happen at once, so they may share keys.
+    fn is_riddle_entry(&self) -> bool {
+        matches!(
+            self,
+            Action::Confirm
+                | Action::DeleteChar
+                | Action::NextChar
+                | Action::PreviousChar
+                | Action::NextSlot
+                | Action::PreviousSlot
+                | Action::FirstSlot
+                | Action::LastSlot
+                | Action::DeleteNextChar
+        )
+    }
+
+    fn conflicts_with(&self, other: Action) -> bool {
+        *self == Action::Cancel
+            || other == Action::Cancel
+            || self.is_riddle_entry() == other.is_riddle_entry()
+    }
+}
+
+/// Which keys and gamepad buttons trigger which action.
+#[derive(Clone, Debug, PartialEq, Resource, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InputMap {
+    bindings: BTreeMap<Action, Vec<KeyCode>>,
+    gamepad_bindings: BTreeMap<Action, Vec<GamepadButton>>,
+}
+
+impl Default for InputMap {
+    fn default() -> Self {
+        Self {
+            bindings: BTreeMap::from_iter([
+                (Action::MoveLeft, vec![KeyCode::ArrowLeft, KeyCode::KeyA]),
+                (Action::MoveRight, vec![KeyCode::ArrowRight, KeyCode::KeyD]),
+                (Action::Jump, vec![KeyCode::ArrowUp, KeyCode::KeyW]),
+                (Action::Interact, vec![KeyCode::Space]),
+                (Action::Confirm, vec![KeyCode::Enter, KeyCode::NumpadEnter]),
+                (Action::Cancel, vec![KeyCode::Escape]),
+                (Action::DeleteChar, vec![KeyCode::Backspace]),
+                (Action::NextChar, vec![KeyCode::ArrowUp]),
+                (Action::PreviousChar, vec![KeyCode::ArrowDown]),
+                (Action::NextSlot, vec![KeyCode::ArrowRight]),
+                (Action::PreviousSlot, vec![KeyCode::ArrowLeft]),
+                (Action::FirstSlot, vec![KeyCode::Home]),
+                (Action::LastSlot, vec![KeyCode::End]),
+                (Action::DeleteNextChar, vec![KeyCode::Delete]),
+            ]),
+            gamepad_bindings: BTreeMap::from_iter([
+                (Action::MoveLeft, vec![GamepadButton::DPadLeft]),
+                (Action::MoveRight, vec![GamepadButton::DPadRight]),
+                (Action::Jump, vec![GamepadButton::South]),
+                (Action::Interact, vec![GamepadButton::West]),
+                (Action::Confirm, vec![GamepadButton::South]),
+                (
+                    Action::Cancel,
+                    vec![GamepadButton::East, GamepadButton::Start],
+                ),
+                (Action::DeleteChar, vec![GamepadButton::West]),
+                (Action::NextChar, vec![GamepadButton::DPadUp]),
+                (Action::PreviousChar, vec![GamepadButton::DPadDown]),
+                (Action::NextSlot, vec![GamepadButton::DPadRight]),
+                (Action::PreviousSlot, vec![GamepadButton::DPadLeft]),
+                (Action::FirstSlot, vec![GamepadButton::LeftTrigger]),
+                (Action::LastSlot, vec![GamepadButton::RightTrigger]),
+                (Action::DeleteNextChar, vec![GamepadButton::North]),
+            ]),
+        }
+    }
+}
+
+impl InputMap {
+    pub fn keys(&self, action: Action) -> &[KeyCode] {
+        self.bindings.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn gamepad_buttons(&self, action: Action) -> &[GamepadButton] {
+        self.gamepad_bindings
+            .get(&action)
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Binds the key to the action alone, so one key never triggers two conflicting actions.
+    ///
+    /// Every action must stay playable from the keyboard, so taking the only key of another
+    /// action fails with that action.
+    pub fn bind(&mut self, action: Action, key: KeyCode) -> Result<(), Action> {
+        if let Some((&unbound, _)) = self.bindings.iter().find(|(bound_action, keys)| {
+            **bound_action != action
+                && action.conflicts_with(**bound_action)
+                && keys.as_slice() == [key]
+        }) {
+            return Err(unbound);
+        }
+        bind_exclusively(&mut self.bindings, action, key);
+        Ok(())
+    }
+
+    /// Binds the gamepad button to the action alone, like `bind` does for keys.
+    pub fn bind_gamepad_button(&mut self, action: Action, button: GamepadButton) {
+        bind_exclusively(&mut self.gamepad_bindings, action, button);
+    }
+}
+
+fn bind_exclusively<T: PartialEq>(
+    bindings: &mut BTreeMap<Action, Vec<T>>,
+    action: Action,
+    input: T,
+) {
+    for (_, inputs) in bindings
+        .iter_mut()
+        .filter(|(bound_action, _)| action.conflicts_with(**bound_action))
+    {
+        inputs.retain(|bound| *bound != input);
+    }
+    bindings.insert(action, vec![input]);
+}
+
+/// The actions held and freshly pressed this frame, as read by gameplay and menus.
+#[derive(Default, Resource)]
+pub struct ActionState {
+    pressed: HashSet<Action>,
+    just_pressed: HashSet<Action>,
+    stick_buttons: HashSet<GamepadButton>,
+    /// Actions held through a switch between walking and answering, ignored until released.
+    consumed: HashSet<Action>,
+}
+
+impl ActionState {
+    pub fn pressed(&self, action: Action) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.just_pressed.contains(&action)
+    }
+
+    /// Stops the action from triggering any other system this frame.
+    pub fn reset(&mut self, action: Action) {
+        self.pressed.remove(&action);
+        self.just_pressed.remove(&action);
+    }
+
+    /// Stops every held action from triggering anything until it is released and pressed again.
+    pub fn consume_pressed(&mut self) {
+        self.consumed.extend(self.pressed.drain());
+        self.just_pressed.clear();
+    }
+}
+
+/// Keys and buttons are shared between walking and answering, so the press that switches
+/// between them must not carry over, like the confirming button making the player jump.
+pub fn consume_pressed_actions_system(mut action_state: ResMut<ActionState>) {
+    action_state.consume_pressed();
+}
+
+/// The d-pad buttons the left stick is pushed towards.
+fn stick_buttons(stick: Vec2) -> HashSet<GamepadButton> {
+    HashSet::from_iter(
+        [
+            (stick.x <= -STICK_THRESHOLD, GamepadButton::DPadLeft),
+            (stick.x >= STICK_THRESHOLD, GamepadButton::DPadRight),
+            (stick.y >= STICK_THRESHOLD, GamepadButton::DPadUp),
+            (stick.y <= -STICK_THRESHOLD, GamepadButton::DPadDown),
+        ]
+        .into_iter()
+        .filter_map(|(pushed, button)| pushed.then_some(button)),
+    )
+}
+
+pub fn update_action_state_system(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    input_map: Res<InputMap>,
+    gamepads: Query<&Gamepad>,
+    mut action_state: ResMut<ActionState>,
+) {
+    let stick_buttons = HashSet::from_iter(
+        gamepads
+            .iter()
+            .flat_map(|gamepad| stick_buttons(gamepad.left_stick())),
+    );
+    let stick_just_pressed = HashSet::from_iter(
+        stick_buttons
+            .difference(&action_state.stick_buttons)
+            .copied(),
+    );
+    let button_pressed = |button: &GamepadButton| {
+        stick_buttons.contains(button) || gamepads.iter().any(|gamepad| gamepad.pressed(*button))
+    };
+    let button_just_pressed = |button: &GamepadButton| {
+        stick_just_pressed.contains(button)
+            || gamepads.iter().any(|gamepad| gamepad.just_pressed(*button))
+    };
+    action_state.pressed.clear();
+    action_state.just_pressed.clear();
+    for action in Action::ALL {
+        let keys = input_map.keys(action).iter().copied();
+        let buttons = input_map.gamepad_buttons(action);
+        if keyboard_input.any_pressed(keys.clone()) || buttons.iter().any(button_pressed) {
+            action_state.pressed.insert(action);
+        }
+        if keyboard_input.any_just_pressed(keys) || buttons.iter().any(button_just_pressed) {
+            action_state.just_pressed.insert(action);
+        }
+    }
+    action_state.stick_buttons = stick_buttons;
+    let ActionState {
+        pressed,
+        just_pressed,
+        consumed,
+        ..
+    } = &mut *action_state;
+    consumed.retain(|action| pressed.contains(action));
+    for action in consumed.iter() {
+        pressed.remove(action);
+        just_pressed.remove(action);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rebound_key_triggers_only_its_new_action() {
+        // Given
+        let mut app = App::new();
+
+        let mut input_map = InputMap::default();
+        input_map.bind(Action::Jump, KeyCode::KeyD).unwrap();
+        let mut input = ButtonInput::<KeyCode>::default();
+        input.press(KeyCode::KeyD);
+        app.insert_resource(input)
+            .insert_resource(input_map)
+            .init_resource::<ActionState>()
+            .add_systems(Update, update_action_state_system);
+
+        // When
+        app.update();
+
+        // Then
+        let action_state = app.world().resource::<ActionState>();
+        assert!(action_state.just_pressed(Action::Jump));
+        assert!(!action_state.pressed(Action::MoveRight));
+        let input_map = app.world().resource::<InputMap>();
+        assert_eq!(input_map.keys(Action::Jump), &[KeyCode::KeyD]);
+        assert_eq!(input_map.keys(Action::MoveRight), &[KeyCode::ArrowRight]);
+    }
+
+    #[test]
+    fn test_binding_the_only_key_of_another_action_is_refused() {
+        // Given
+        let mut input_map = InputMap::default();
+
+        // When
+        let result = input_map.bind(Action::Jump, KeyCode::Escape);
+
+        // Then
+        assert_eq!(result, Err(Action::Cancel));
+        assert_eq!(input_map, InputMap::default());
+    }
+
+    #[test]
+    fn test_gamepad_stick_acts_like_the_dpad() {
+        // Given
+        let mut app = App::new();
+
+        app.init_resource::<ButtonInput<KeyCode>>()
+            .init_resource::<InputMap>()
+            .init_resource::<ActionState>()
+            .add_systems(Update, update_action_state_system);
+        let mut gamepad = Gamepad::default();
+        gamepad.analog_mut().set(GamepadAxis::LeftStickX, -0.8);
+        gamepad.digital_mut().press(GamepadButton::South);
+        let gamepad = app.world_mut().spawn(gamepad).id();
+
+        // When
+        app.update();
+
+        // Then
+        let action_state = app.world().resource::<ActionState>();
+        assert!(action_state.pressed(Action::MoveLeft));
+        assert!(action_state.just_pressed(Action::PreviousSlot));
+        assert!(action_state.just_pressed(Action::Jump));
+        assert!(!action_state.pressed(Action::MoveRight));
+
+        // When
+        app.world_mut()
+            .get_mut::<Gamepad>(gamepad)
+            .unwrap()
+            .digital_mut()
+            .clear();
+        app.update();
+
+        // Then
+        let action_state = app.world().resource::<ActionState>();
+        assert!(action_state.pressed(Action::MoveLeft));
+        assert!(!action_state.just_pressed(Action::PreviousSlot));
+        assert!(action_state.pressed(Action::Jump));
+    }
+
+    #[test]
+    fn test_consumed_actions_wait_for_a_new_press() {
+        // Given
+        let mut app = App::new();
+
+        let mut input = ButtonInput::<KeyCode>::default();
+        input.press(KeyCode::Enter);
+        app.insert_resource(input)
+            .init_resource::<InputMap>()
+            .init_resource::<ActionState>()
+            .add_systems(Update, update_action_state_system);
+        app.update();
+
+        // When
+        app.world_mut()
+            .resource_mut::<ActionState>()
+            .consume_pressed();
+        app.world_mut()
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .clear();
+        app.update();
+
+        // Then
+        assert!(!app
+            .world()
+            .resource::<ActionState>()
+            .pressed(Action::Confirm));
+
+        // When
+        let mut input = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+        input.release(KeyCode::Enter);
+        app.update();
+        app.world_mut()
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .press(KeyCode::Enter);
+        app.update();
+
+        // Then
+        assert!(app
+            .world()
+            .resource::<ActionState>()
+            .just_pressed(Action::Confirm));
+    }
+
+    #[test]
+    fn test_rebound_gamepad_button_triggers_only_its_new_action() {
+        // Given
+        let mut input_map = InputMap::default();
+
+        // When
+        input_map.bind_gamepad_button(Action::Interact, GamepadButton::South);
+
+        // Then
+        assert_eq!(
+            input_map.gamepad_buttons(Action::Interact),
+            &[GamepadButton::South]
+        );
+        assert!(input_map.gamepad_buttons(Action::Jump).is_empty());
+        assert_eq!(
+            input_map.gamepad_buttons(Action::Confirm),
+            &[GamepadButton::South]
+        );
+    }
+}
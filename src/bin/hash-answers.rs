@@ -0,0 +1,204 @@
+//! Replaces the plaintext riddle answers in an LDtk map with salted hashes.
+//!
+//! Usage: `cargo run --bin hash-answers [path/to/map.ldtk]`
+//!
+//! Every door whose `answer_hashed` flag is off gets its `answer` and `answers` hashed
+//! after normalization, salted with the door IID, its `answer_length` and
+//! `shortest_answer_length` set to the longest and shortest answer, and the flag turned on. Doors with the flag on are skipped, so running the tool twice changes nothing.
+
+use puzzle_up::riddles::answer::{hash_answer, AnswerKind, AnswerNormalization};
+use serde_json::{json, Value};
+use std::{env, fs};
+
+const DEFAULT_MAP_PATH: &str = "assets/map.ldtk";
+
+fn field<'a>(door: &'a Value, identifier: &str) -> Option<&'a Value> {
+    door["fieldInstances"]
+        .as_array()?
+        .iter()
+        .find(|field| field["__identifier"] == identifier)
+        .map(|field| &field["__value"])
+}
+
+fn set_field(door: &mut Value, identifier: &str, value: Value, editor_values: Value) {
+    let door_iid = door["iid"].clone();
+    let field = door["fieldInstances"]
+        .as_array_mut()
+        .and_then(|fields| {
+            fields
+                .iter_mut()
+                .find(|field| field["__identifier"] == identifier)
+        })
+        .unwrap_or_else(|| {
+            panic!("The door {door_iid} has no {identifier} field, open and save the map in LDtk first!")
+        });
+    field["__value"] = value;
+    field["realEditorValues"] = editor_values;
+}
+
+fn strings(value: Option<&Value>) -> Vec<String> {
+    value
+        .and_then(Value::as_array)
+        .map(|values| {
+            values
+                .iter()
+                .filter_map(|value| value.as_str().map(String::from))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Hashes the answers of one door, returning whether anything changed.
+fn hash_door(door: &mut Value) -> bool {
+    if field(door, "answer_hashed") == Some(&Value::Bool(true)) {
+        return false;
+    }
+    let salt = door["iid"]
+        .as_str()
+        .expect("Every LDtk entity has an IID!")
+        .to_string();
+    let answer_kind = field(door, "answer_kind")
+        .and_then(Value::as_str)
+        .map(|kind| AnswerKind::try_from(kind).expect("The answer kind should be known!"))
+        .unwrap_or_default();
+    let normalization = strings(field(door, "normalization"))
+        .iter()
+        .try_fold(
+            AnswerNormalization::for_kind(answer_kind),
+            |normalization, step| normalization.try_with_step(step),
+        )
+        .unwrap_or_else(|error| panic!("{error}!"));
+    let answer = field(door, "answer")
+        .and_then(Value::as_str)
+        .expect("An answer is required for a riddle!")
+        .to_string();
+    let answers = strings(field(door, "answers"));
+    let answer_lengths = Vec::from_iter(
+        [&answer]
+            .into_iter()
+            .chain(answers.iter())
+            .map(|answer| answer.chars().count()),
+    );
+    let answer_length = answer_lengths.iter().copied().max().unwrap_or_default();
+    let shortest_answer_length = answer_lengths.iter().copied().min().unwrap_or_default();
+    let hash = |answer: &str| hash_answer(&salt, &normalization.normalize(answer));
+    let string_value = |value: &str| json!({ "id": "V_String", "params": [value] });
+
+    let answer = hash(&answer);
+    set_field(
+        door,
+        "answer",
+        json!(answer),
+        json!([string_value(&answer)]),
+    );
+    let answers = Vec::from_iter(answers.iter().map(|answer| hash(answer)));
+    let editor_values = Vec::from_iter(answers.iter().map(|answer| string_value(answer)));
+    set_field(door, "answers", json!(answers), json!(editor_values));
+    set_field(
+        door,
+        "answer_length",
+        json!(answer_length),
+        json!([{ "id": "V_Int", "params": [answer_length] }]),
+    );
+    set_field(
+        door,
+        "shortest_answer_length",
+        json!(shortest_answer_length),
+        json!([{ "id": "V_Int", "params": [shortest_answer_length] }]),
+    );
+    set_field(
+        door,
+        "answer_hashed",
+        json!(true),
+        json!([{ "id": "V_Bool", "params": [true] }]),
+    );
+    true
+}
+
+fn main() {
+    let path = env::args()
+        .nth(1)
+        .unwrap_or_else(|| DEFAULT_MAP_PATH.to_string());
+    let content = fs::read_to_string(&path).expect("The map file should be readable!");
+    let mut project: Value =
+        serde_json::from_str(&content).expect("The map file should be valid JSON!");
+
+    let mut hashed = 0;
+    let mut skipped = 0;
+    for level in project["levels"].as_array_mut().into_iter().flatten() {
+        for layer in level["layerInstances"].as_array_mut().into_iter().flatten() {
+            for entity in layer["entityInstances"]
+                .as_array_mut()
+                .into_iter()
+                .flatten()
+                .filter(|entity| entity["__identifier"] == "Door")
+            {
+                if hash_door(entity) {
+                    hashed += 1;
+                } else {
+                    skipped += 1;
+                }
+            }
+        }
+    }
+
+    let mut output = Vec::new();
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(b"\t");
+    let mut serializer = serde_json::Serializer::with_formatter(&mut output, formatter);
+    serde::Serialize::serialize(&project, &mut serializer)
+        .expect("The map is always serializable!");
+    fs::write(&path, output).expect("The map file should be writable!");
+    println!("Hashed the answers of {hashed} doors, {skipped} were already hashed.");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_ecs_ldtk::ldtk::EntityInstance;
+    use puzzle_up::{map::TryFromEntity, riddles::RiddleInfo};
+
+    fn shipped_door() -> Value {
+        let project: Value =
+            serde_json::from_str(&fs::read_to_string(DEFAULT_MAP_PATH).unwrap()).unwrap();
+        project["levels"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .flat_map(|level| level["layerInstances"].as_array().into_iter().flatten())
+            .flat_map(|layer| layer["entityInstances"].as_array().into_iter().flatten())
+            .find(|entity| entity["__identifier"] == "Door")
+            .cloned()
+            .expect("The shipped map should have a door!")
+    }
+
+    #[test]
+    fn test_hashed_door_accepts_its_shortest_answer() {
+        // Given
+        let mut door = shipped_door();
+        let string_value = |value: &str| json!({ "id": "V_String", "params": [value] });
+        set_field(&mut door, "answer_kind", json!("Letters"), json!([]));
+        set_field(
+            &mut door,
+            "answer",
+            json!("silver"),
+            json!([string_value("silver")]),
+        );
+        set_field(
+            &mut door,
+            "answers",
+            json!(["gold"]),
+            json!([string_value("gold")]),
+        );
+
+        // When
+        let hashed = hash_door(&mut door);
+        let entity_instance: EntityInstance = serde_json::from_value(door).unwrap();
+        let riddle = RiddleInfo::try_from_entity(&entity_instance).unwrap();
+
+        // Then
+        assert!(hashed);
+        assert!(riddle.solves("gold"));
+        assert!(riddle.solves("Silver"));
+        assert!(!riddle.solves("bronze"));
+    }
+}
@@ -0,0 +1,52 @@
+//! Prints the levels of an LDtk map and the doors between them as a graph.
+//!
+//! Usage: `cargo run --bin level-graph [--mermaid] [path/to/map.ldtk]`
+//!
+//! The graph is Graphviz DOT unless `--mermaid` is given. Unreachable levels, dead ends and
+//! cycles are highlighted in the graph and listed on stderr, along with the doors left out for
+//! being invalid. The exit code is non-zero for an unknown flag or any invalid door.
+
+use bevy_ecs_ldtk::ldtk::LdtkJson;
+use puzzle_up::map::{graph::LevelGraph, STARTING_LEVEL};
+use std::{env, fs, process::ExitCode};
+
+const DEFAULT_MAP_PATH: &str = "assets/map.ldtk";
+
+fn main() -> ExitCode {
+    let (flags, paths): (Vec<String>, Vec<String>) =
+        env::args().skip(1).partition(|arg| arg.starts_with("--"));
+    if let Some(flag) = flags.iter().find(|flag| *flag != "--mermaid") {
+        eprintln!("unknown flag {flag}, usage: level-graph [--mermaid] [path/to/map.ldtk]");
+        return ExitCode::FAILURE;
+    }
+    let mermaid = !flags.is_empty();
+    let path = paths
+        .into_iter()
+        .next()
+        .unwrap_or_else(|| DEFAULT_MAP_PATH.to_string());
+    let project: LdtkJson = match fs::read_to_string(&path)
+        .map_err(|error| error.to_string())
+        .and_then(|content| serde_json::from_str(&content).map_err(|error| error.to_string()))
+    {
+        Ok(project) => project,
+        Err(error) => {
+            eprintln!("{path}: {error}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let graph = LevelGraph::from_project(&project);
+    if mermaid {
+        print!("{}", graph.to_mermaid(STARTING_LEVEL));
+    } else {
+        print!("{}", graph.to_dot(STARTING_LEVEL));
+    }
+    for problem in graph.problems(STARTING_LEVEL) {
+        eprintln!("{path}: {problem}");
+    }
+    if graph.invalid_doors.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
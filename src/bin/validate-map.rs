@@ -0,0 +1,40 @@
+//! Checks every entity in an LDtk map the way the game would spawn it.
+//!
+//! Usage: `cargo run --bin validate-map [path/to/map.ldtk]`
+//!
+//! Each problem is printed as `file:level:entity: error`, and the exit code is non-zero
+//! when there is at least one.
+
+use bevy_ecs_ldtk::ldtk::LdtkJson;
+use puzzle_up::map::validation::validate_project;
+use std::{env, fs, process::ExitCode};
+
+const DEFAULT_MAP_PATH: &str = "assets/map.ldtk";
+
+fn main() -> ExitCode {
+    let path = env::args()
+        .nth(1)
+        .unwrap_or_else(|| DEFAULT_MAP_PATH.to_string());
+    let project: LdtkJson = match fs::read_to_string(&path)
+        .map_err(|error| error.to_string())
+        .and_then(|content| serde_json::from_str(&content).map_err(|error| error.to_string()))
+    {
+        Ok(project) => project,
+        Err(error) => {
+            eprintln!("{path}: {error}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let problems = validate_project(&path, &project);
+    for problem in problems.iter() {
+        eprintln!("{problem}");
+    }
+    if problems.is_empty() {
+        println!("{path}: {} levels, no problems found", project.levels.len());
+        ExitCode::SUCCESS
+    } else {
+        eprintln!("{path}: {} problems found", problems.len());
+        ExitCode::FAILURE
+    }
+}
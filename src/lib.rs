@@ -0,0 +1,127 @@
+use actions::ActionsPlugin;
+use bevy::{app::PluginGroupBuilder, prelude::*};
+use bevy_rapier2d::prelude::*;
+use map::MapPlugin;
+use menu::MenuPlugin;
+use physics::PhysicsPlugin;
+use player::PlayerPlugin;
+use riddles::RiddlesPlugin;
+use save::SavePlugin;
+use settings::SettingsPlugin;
+
+pub mod actions;
+pub mod map;
+mod menu;
+pub mod physics;
+pub mod player;
+pub mod replay;
+pub mod riddles;
+mod save;
+mod settings;
+
+pub use settings::AudioChannel;
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash, States)]
+pub enum GameState {
+    MainMenu,
+    SlotSelecting,
+    Settings,
+    Controls,
+    MapExploring,
+    RiddleSolving,
+    LevelLoading,
+    Paused,
+}
+
+/// Everything the game adds on top of Bevy's default plugins.
+pub struct GamePlugin;
+
+impl Plugin for GamePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_state(GameState::MainMenu)
+            .add_plugins(GameplayPlugins)
+            .add_plugins(SavePlugin)
+            .add_plugins(MenuPlugin)
+            .add_plugins(SettingsPlugin);
+    }
+}
+
+/// The levels, the player and the riddles, without the menus, saves and settings around them.
+pub struct GameplayPlugins;
+
+impl PluginGroup for GameplayPlugins {
+    fn build(self) -> PluginGroupBuilder {
+        PluginGroupBuilder::start::<Self>()
+            .add(SetupPlugin)
+            .add(PhysicsPlugin)
+            .add(MapPlugin)
+            .add(ActionsPlugin)
+            .add(PlayerPlugin)
+            .add(RiddlesPlugin)
+    }
+}
+
+struct SetupPlugin;
+
+impl Plugin for SetupPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup_system);
+    }
+}
+
+/// Vertical acceleration of every dynamic body, in pixels per second squared.
+pub const GRAVITY: f32 = -400.0;
+
+fn setup_system(mut commands: Commands, mut rapier_config: Query<&mut RapierConfiguration>) {
+    commands.spawn(Camera2d);
+    rapier_config.single_mut().gravity = Vec2::new(0.0, GRAVITY);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_setup_system_spawns_camera() {
+        // Given
+        let mut app = App::new();
+
+        app.world_mut().spawn(RapierConfiguration::new(100.0));
+
+        // When
+        app.add_systems(Startup, setup_system);
+        app.update();
+
+        // Then
+        let cameras = app.world_mut().query::<&Camera2d>().iter(app.world()).len();
+        assert_eq!(cameras, 1);
+    }
+
+    #[test]
+    fn test_setup_system_updates_gravity() {
+        // Given
+        let mut app = App::new();
+
+        let rapier_configuration = app
+            .world_mut()
+            .spawn(RapierConfiguration {
+                gravity: Vec2::new(0.0, 0.0),
+                physics_pipeline_active: true,
+                query_pipeline_active: true,
+                scaled_shape_subdivision: 0,
+                force_update_from_transform_changes: true,
+            })
+            .id();
+
+        // When
+        app.add_systems(Startup, setup_system);
+        app.update();
+
+        // Then
+        let rapier_configuration = app
+            .world()
+            .get::<RapierConfiguration>(rapier_configuration)
+            .unwrap();
+        assert_eq!(rapier_configuration.gravity, Vec2::new(0.0, -400.0));
+    }
+}
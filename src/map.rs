@@ -1,27 +1,51 @@
 use crate::{riddles::RiddleInfo, GameState};
-use bevy::prelude::*;
+use bevy::{app::SubApp, prelude::*, render::RenderApp};
 use bevy_ecs_ldtk::prelude::*;
 use bevy_rapier2d::prelude::*;
+use placeholder::invalid_entity_system;
+pub use placeholder::InvalidEntity;
 use text::*;
+pub use validation::{EntityError, TryFromEntity};
 
+pub mod graph;
+mod placeholder;
+pub mod reachability;
 mod text;
+pub mod validation;
 
 pub struct MapPlugin;
 
 impl Plugin for MapPlugin {
     fn build(&self, app: &mut App) {
+        // `TilemapPlugin` expects a render app while it builds, which headless apps without a GPU
+        // lack. Every later step checks whether it exists, so an empty one is enough to get by.
+        let headless = app.get_sub_app(RenderApp).is_none();
+        if headless {
+            app.insert_sub_app(RenderApp, SubApp::new());
+        }
+        app.add_plugins(LdtkPlugin);
+        if headless {
+            app.remove_sub_app(RenderApp);
+        }
         app.insert_resource(LevelSelection::iid(STARTING_LEVEL))
             .add_systems(Startup, zone_text_setup_system)
-            .add_systems(OnEnter(GameState::LevelLoading), level_loading_system)
+            .add_systems(
+                OnEnter(GameState::LevelLoading),
+                (map_setup_system, level_loading_system).chain(),
+            )
             .add_systems(
                 OnExit(GameState::LevelLoading),
                 (center_map_system, normalize_font_system),
             )
-            .add_systems(Startup, map_setup_system)
+            .add_systems(
+                OnEnter(GameState::MainMenu),
+                (map_cleanup_system, hide_zone_text_on_exit_system),
+            )
             .add_systems(
                 Update,
                 (
                     level_loaded_system.run_if(in_state(GameState::LevelLoading)),
+                    invalid_entity_system,
                     (show_zone_text_system, hide_zone_text_system)
                         .run_if(in_state(GameState::MapExploring)),
                 ),
@@ -35,17 +59,30 @@ impl Plugin for MapPlugin {
     }
 }
 
-const STARTING_LEVEL: &str = "27e654c0-ed50-11ed-9ee3-a3abea3fe6ae";
+pub const STARTING_LEVEL: &str = "27e654c0-ed50-11ed-9ee3-a3abea3fe6ae";
 const SMALL_TILE_SIZE: f32 = 16.0;
 const LARGE_TILE_SIZE: f32 = 64.0;
 
-fn map_setup_system(mut commands: Commands, asset_server: Res<AssetServer>) {
+fn map_setup_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    ldtk_worlds: Query<(), With<LdtkProjectHandle>>,
+) {
+    if !ldtk_worlds.is_empty() {
+        return;
+    }
     commands.spawn(LdtkWorldBundle {
         ldtk_handle: asset_server.load("map.ldtk").into(),
         ..default()
     });
 }
 
+fn map_cleanup_system(mut commands: Commands, ldtk_worlds: Query<Entity, With<LdtkProjectHandle>>) {
+    for world in ldtk_worlds.iter() {
+        commands.entity(world).despawn_recursive();
+    }
+}
+
 fn level_loading_system(
     level_selection: Res<LevelSelection>,
     mut level_set_info: Query<&mut LevelSet>,
@@ -106,13 +143,6 @@ struct GroundTile {
     ground: Ground,
 }
 
-#[derive(Default, Bundle, LdtkEntity)]
-struct LevelBorder {
-    #[from_entity_instance]
-    #[bundle()]
-    collider_bundle: ColliderBundle,
-}
-
 #[derive(Default, Bundle, LdtkEntity)]
 struct BoxTile {
     #[sprite_sheet]
@@ -0,0 +1,370 @@
+use super::validation::validate_entity;
+use crate::riddles::RiddleInfo;
+use bevy::utils::{HashMap, HashSet};
+use bevy_ecs_ldtk::ldtk::LdtkJson;
+use std::fmt::Write;
+
+/// A door leading from one level to another, by index into `LevelGraph::levels`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LevelLink {
+    pub from: usize,
+    pub to: usize,
+    pub question: String,
+}
+
+/// The levels of a map and the doors between them.
+///
+/// Doors that `validate-map` would reject are left out and listed in `invalid_doors`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LevelGraph {
+    pub levels: Vec<String>,
+    level_iids: Vec<String>,
+    pub links: Vec<LevelLink>,
+    /// Why each left out door was rejected, as `level:entity: error`.
+    pub invalid_doors: Vec<String>,
+}
+
+impl LevelGraph {
+    pub fn from_project(project: &LdtkJson) -> Self {
+        let indices = HashMap::from_iter(
+            project
+                .levels
+                .iter()
+                .enumerate()
+                .map(|(index, level)| (level.iid.as_str(), index)),
+        );
+        let level_iids = HashSet::from_iter(indices.keys().copied());
+        let mut links = Vec::new();
+        let mut invalid_doors = Vec::new();
+        for (from, level) in project.levels.iter().enumerate() {
+            for entity_instance in level
+                .layer_instances
+                .iter()
+                .flatten()
+                .flat_map(|layer| layer.entity_instances.iter())
+                .filter(|entity_instance| entity_instance.identifier == "Door")
+            {
+                if let Err(error) = validate_entity(entity_instance, &level_iids) {
+                    invalid_doors.push(format!(
+                        "{}:Door({}): {error}",
+                        level.identifier, entity_instance.iid
+                    ));
+                    continue;
+                }
+                let riddle_info = RiddleInfo::from(entity_instance);
+                let to = indices[riddle_info.next_level()];
+                links.push(LevelLink {
+                    from,
+                    to,
+                    question: riddle_info.question().to_string(),
+                });
+            }
+        }
+        Self {
+            levels: Vec::from_iter(project.levels.iter().map(|level| level.identifier.clone())),
+            level_iids: Vec::from_iter(project.levels.iter().map(|level| level.iid.clone())),
+            links,
+            invalid_doors,
+        }
+    }
+
+    fn index_of(&self, level_iid: &str) -> Option<usize> {
+        self.level_iids.iter().position(|iid| iid == level_iid)
+    }
+
+    fn reachable_from(&self, start: usize) -> Vec<bool> {
+        let mut reached = vec![false; self.levels.len()];
+        let mut pending = vec![start];
+        while let Some(level) = pending.pop() {
+            if std::mem::replace(&mut reached[level], true) {
+                continue;
+            }
+            pending.extend(
+                self.links
+                    .iter()
+                    .filter(|link| link.from == level)
+                    .map(|link| link.to),
+            );
+        }
+        reached
+    }
+
+    /// Levels no chain of doors leads to from the starting level.
+    pub fn unreachable_levels(&self, start_iid: &str) -> Vec<usize> {
+        let Some(start) = self.index_of(start_iid) else {
+            return Vec::from_iter(0..self.levels.len());
+        };
+        let reached = self.reachable_from(start);
+        Vec::from_iter((0..self.levels.len()).filter(|&level| !reached[level]))
+    }
+
+    /// Levels without a single door out of them.
+    pub fn dead_ends(&self) -> Vec<usize> {
+        Vec::from_iter(
+            (0..self.levels.len())
+                .filter(|&level| !self.links.iter().any(|link| link.from == level)),
+        )
+    }
+
+    /// Groups of levels the player can go around in, in the order of their first level.
+    pub fn cycles(&self) -> Vec<Vec<usize>> {
+        let reached =
+            Vec::from_iter((0..self.levels.len()).map(|level| self.reachable_from(level)));
+        let mut cycles: Vec<Vec<usize>> = Vec::new();
+        for (level, reached_from_level) in reached.iter().enumerate() {
+            if cycles.iter().any(|cycle| cycle.contains(&level)) {
+                continue;
+            }
+            let cycle = Vec::from_iter(
+                (0..self.levels.len())
+                    .filter(|&other| reached_from_level[other] && reached[other][level]),
+            );
+            let loops_back = self
+                .links
+                .iter()
+                .any(|link| link.from == level && link.to == level);
+            if cycle.len() > 1 || loops_back {
+                cycles.push(cycle);
+            }
+        }
+        cycles
+    }
+
+    fn flags(&self, start_iid: &str) -> Vec<Vec<&'static str>> {
+        let mut flags = vec![Vec::new(); self.levels.len()];
+        if let Some(start) = self.index_of(start_iid) {
+            flags[start].push("start");
+        }
+        for level in self.unreachable_levels(start_iid) {
+            flags[level].push("unreachable");
+        }
+        for level in self.dead_ends() {
+            flags[level].push("dead_end");
+        }
+        for level in self.cycles().into_iter().flatten() {
+            flags[level].push("cycle");
+        }
+        flags
+    }
+
+    /// Describes every left out door and flagged level on its own line, e.g. `Level_1: dead end`.
+    pub fn problems(&self, start_iid: &str) -> Vec<String> {
+        let mut problems = self.invalid_doors.clone();
+        for level in self.unreachable_levels(start_iid) {
+            problems.push(format!(
+                "{}: unreachable from the start",
+                self.levels[level]
+            ));
+        }
+        for level in self.dead_ends() {
+            problems.push(format!("{}: dead end", self.levels[level]));
+        }
+        for cycle in self.cycles() {
+            let levels = Vec::from_iter(cycle.iter().map(|&level| self.levels[level].as_str()));
+            problems.push(format!("{}: cycle", levels.join(" <-> ")));
+        }
+        problems
+    }
+
+    pub fn to_dot(&self, start_iid: &str) -> String {
+        let mut dot = "digraph levels {\n".to_string();
+        for (level, flags) in self.flags(start_iid).into_iter().enumerate() {
+            let mut attributes = vec![format!("label=\"{}\"", escape_dot(&self.levels[level]))];
+            if flags.contains(&"start") {
+                attributes.push("shape=doublecircle".to_string());
+            }
+            if flags.contains(&"unreachable") {
+                attributes.push("style=dashed".to_string());
+            }
+            if flags.contains(&"dead_end") {
+                attributes.push("color=red".to_string());
+            } else if flags.contains(&"cycle") {
+                attributes.push("color=orange".to_string());
+            }
+            writeln!(dot, "    L{level} [{}];", attributes.join(", ")).unwrap();
+        }
+        for link in self.links.iter() {
+            writeln!(
+                dot,
+                "    L{} -> L{} [label=\"{}\"];",
+                link.from,
+                link.to,
+                escape_dot(&link.question)
+            )
+            .unwrap();
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    pub fn to_mermaid(&self, start_iid: &str) -> String {
+        let mut mermaid = "flowchart TD\n".to_string();
+        for (level, identifier) in self.levels.iter().enumerate() {
+            writeln!(mermaid, "    L{level}[\"{}\"]", escape_mermaid(identifier)).unwrap();
+        }
+        for link in self.links.iter() {
+            writeln!(
+                mermaid,
+                "    L{} -->|\"{}\"| L{}",
+                link.from,
+                escape_mermaid(&link.question),
+                link.to
+            )
+            .unwrap();
+        }
+        mermaid.push_str("    classDef start stroke-width:4px\n");
+        mermaid.push_str("    classDef unreachable stroke-dasharray:5 5\n");
+        mermaid.push_str("    classDef dead_end stroke:red\n");
+        mermaid.push_str("    classDef cycle stroke:orange\n");
+        for (level, flags) in self.flags(start_iid).into_iter().enumerate() {
+            for flag in flags {
+                writeln!(mermaid, "    class L{level} {flag}").unwrap();
+            }
+        }
+        mermaid
+    }
+}
+
+fn escape_dot(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn escape_mermaid(text: &str) -> String {
+    text.replace('"', "#quot;").replace('\n', "<br>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::prelude::default;
+    use bevy_ecs_ldtk::{
+        ldtk::{FieldInstance, Level},
+        prelude::*,
+    };
+
+    fn door(question: &str, next_level: &str) -> EntityInstance {
+        let field = |identifier: &str, value: &str| FieldInstance {
+            identifier: identifier.to_string(),
+            tile: None,
+            field_instance_type: String::new(),
+            value: FieldValue::String(Some(value.to_string())),
+            def_uid: 0,
+            real_editor_values: Vec::new(),
+        };
+        EntityInstance {
+            identifier: "Door".to_string(),
+            field_instances: vec![
+                field("question", question),
+                field("answer", "4"),
+                field("next_level", next_level),
+            ],
+            ..default()
+        }
+    }
+
+    fn level(iid: &str, doors: Vec<EntityInstance>) -> Level {
+        Level {
+            identifier: format!("Level_{iid}"),
+            iid: iid.to_string(),
+            layer_instances: Some(vec![LayerInstance {
+                entity_instances: doors,
+                ..default()
+            }]),
+            ..default()
+        }
+    }
+
+    fn project() -> LdtkJson {
+        LdtkJson {
+            levels: vec![
+                level("0", vec![door("To one?", "1"), door("To two?", "2")]),
+                level("1", vec![door("Back?", "0")]),
+                level("2", vec![]),
+                level("3", vec![door("To two?", "2")]),
+            ],
+            ..default()
+        }
+    }
+
+    #[test]
+    fn test_level_graph_flags_unreachable_levels_dead_ends_and_cycles() {
+        // Given
+        let graph = LevelGraph::from_project(&project());
+
+        // When
+        let problems = graph.problems("0");
+
+        // Then
+        assert_eq!(
+            problems,
+            vec![
+                "Level_3: unreachable from the start",
+                "Level_2: dead end",
+                "Level_0 <-> Level_1: cycle",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_level_graph_reports_the_doors_it_leaves_out() {
+        // Given
+        let mut broken_door = door("To one?", "1");
+        broken_door.iid = "broken".to_string();
+        broken_door
+            .field_instances
+            .retain(|field| field.identifier != "answer");
+        let mut lost_door = door("To nowhere?", "9");
+        lost_door.iid = "lost".to_string();
+        let graph = LevelGraph::from_project(&LdtkJson {
+            levels: vec![
+                level("0", vec![broken_door, lost_door, door("To one?", "1")]),
+                level("1", vec![door("Back?", "0")]),
+            ],
+            ..default()
+        });
+
+        // When
+        let problems = graph.problems("0");
+
+        // Then
+        assert_eq!(graph.links.len(), 2);
+        assert_eq!(
+            problems,
+            vec![
+                "Level_0:Door(broken): missing required field answer",
+                "Level_0:Door(lost): invalid field next_level: 9 is not the IID of a level",
+                "Level_0 <-> Level_1: cycle",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_level_graph_exports_doors_as_labelled_edges() {
+        // Given
+        let graph = LevelGraph::from_project(&LdtkJson {
+            levels: vec![
+                level("0", vec![door("Say \"hi\"\nto me?", "1")]),
+                level("1", vec![]),
+            ],
+            ..default()
+        });
+
+        // When
+        let dot = graph.to_dot("0");
+        let mermaid = graph.to_mermaid("0");
+
+        // Then
+        assert_eq!(
+            dot,
+            "digraph levels {\n    \
+                L0 [label=\"Level_0\", shape=doublecircle];\n    \
+                L1 [label=\"Level_1\", color=red];\n    \
+                L0 -> L1 [label=\"Say \\\"hi\\\"\\nto me?\"];\n\
+            }\n"
+        );
+        assert!(mermaid.contains("    L0 -->|\"Say #quot;hi#quot;<br>to me?\"| L1\n"));
+        assert!(mermaid.contains("    class L1 dead_end\n"));
+    }
+}
@@ -0,0 +1,145 @@
+use super::{
+    text::{TextInfo, TextSign},
+    validation::check_entity,
+    EntityError, LARGE_TILE_SIZE,
+};
+use crate::riddles::RiddleInfo;
+use bevy::{prelude::*, text::TextBounds};
+use bevy_ecs_ldtk::prelude::*;
+
+const PLACEHOLDER_COLOR: Color = Color::srgb(1.0, 0.0, 1.0);
+
+/// Marks an LDtk entity whose fields could not be converted, with the reason.
+#[derive(Component)]
+pub struct InvalidEntity {
+    pub error: EntityError,
+}
+
+fn placeholder_size(entity_instance: &EntityInstance) -> Vec2 {
+    if entity_instance.width > 0 && entity_instance.height > 0 {
+        Vec2::new(entity_instance.width as f32, entity_instance.height as f32)
+    } else {
+        Vec2::splat(LARGE_TILE_SIZE)
+    }
+}
+
+fn placeholder_box(size: Vec2) -> (Sprite, Transform) {
+    (
+        Sprite::from_color(PLACEHOLDER_COLOR, size),
+        Transform::from_xyz(0.0, 0.0, 1.0),
+    )
+}
+
+fn placeholder_text(error: &EntityError, size: Vec2) -> (Text2d, TextFont, TextBounds, Transform) {
+    (
+        Text2d::new(error.to_string()),
+        TextFont {
+            font_size: 10.0,
+            ..default()
+        },
+        TextBounds::new(size.x, size.y),
+        Transform::from_xyz(0.0, 0.0, 1.0),
+    )
+}
+
+/// Swaps entities with bad fields for a magenta box showing the error, so the map still loads.
+pub fn invalid_entity_system(
+    mut commands: Commands,
+    entities: Query<(Entity, &EntityInstance), Added<EntityInstance>>,
+) {
+    for (entity, entity_instance) in entities.iter() {
+        let Err(error) = check_entity(entity_instance) else {
+            continue;
+        };
+        warn!(
+            identifier = entity_instance.identifier,
+            iid = entity_instance.iid,
+            error = %error,
+            "Invalid LDtk entity replaced by a placeholder"
+        );
+        let size = placeholder_size(entity_instance);
+        commands
+            .entity(entity)
+            .remove::<(RiddleInfo, TextInfo, TextSign)>()
+            .with_children(|parent| {
+                parent.spawn(placeholder_box(size)).with_children(|parent| {
+                    parent.spawn(placeholder_text(&error, size));
+                });
+            })
+            .insert(InvalidEntity { error });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_ecs_ldtk::ldtk::FieldInstance;
+
+    #[test]
+    fn test_invalid_door_is_replaced_by_a_placeholder() {
+        // Given
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_systems(Update, invalid_entity_system);
+        let entity_instance = EntityInstance {
+            identifier: "Door".to_string(),
+            iid: "broken-door".to_string(),
+            width: 64,
+            height: 64,
+            field_instances: vec![FieldInstance {
+                identifier: "answer".to_string(),
+                tile: None,
+                field_instance_type: String::new(),
+                value: FieldValue::String(Some("4".to_string())),
+                def_uid: 0,
+                real_editor_values: Vec::new(),
+            }],
+            ..default()
+        };
+        let door = app
+            .world_mut()
+            .spawn((RiddleInfo::from(&entity_instance), entity_instance))
+            .id();
+
+        // When
+        app.update();
+
+        // Then
+        assert!(app.world().get::<RiddleInfo>(door).is_none());
+        assert_eq!(
+            app.world().get::<InvalidEntity>(door).unwrap().error,
+            EntityError::MissingField("question")
+        );
+        let placeholder = app.world().get::<Children>(door).unwrap()[0];
+        let sprite = app.world().get::<Sprite>(placeholder).unwrap();
+        assert_eq!(sprite.color, PLACEHOLDER_COLOR);
+        assert_eq!(sprite.custom_size, Some(Vec2::splat(64.0)));
+        let label = app.world().get::<Children>(placeholder).unwrap()[0];
+        assert_eq!(
+            app.world().get::<Text2d>(label).unwrap().0,
+            "missing required field question"
+        );
+    }
+
+    #[test]
+    fn test_valid_entities_are_left_alone() {
+        // Given
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_systems(Update, invalid_entity_system);
+        let ground = app
+            .world_mut()
+            .spawn(EntityInstance {
+                identifier: "Ground".to_string(),
+                ..default()
+            })
+            .id();
+
+        // When
+        app.update();
+
+        // Then
+        assert!(app.world().get::<InvalidEntity>(ground).is_none());
+        assert!(app.world().get::<Children>(ground).is_none());
+    }
+}
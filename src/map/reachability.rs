@@ -0,0 +1,335 @@
+//! Works out which doors and zone texts the player can get to, without running the game.
+//!
+//! The player is simulated tick by tick the way `player_movement_system` and Rapier move it:
+//! running speeds the player up towards the run speed, jumping sets the vertical speed and
+//! gravity pulls it back down. Like the movement system, a jump is allowed while standing on a
+//! ground tile or within the coyote time after leaving one, and letting go of the jump key on
+//! the way up cuts the jump short. Every combination of inputs, each held for a few ticks, is
+//! explored from the spawn point.
+
+use crate::{
+    physics::DEFAULT_TICK_RATE,
+    player::{MovementTuning, JUMP_POWER, PLAYER_HEIGHT, PLAYER_WIDTH},
+    GRAVITY,
+};
+use bevy::{math::Rect, prelude::*, utils::HashSet};
+use bevy_ecs_ldtk::ldtk::{LdtkJson, Level};
+use std::collections::VecDeque;
+
+const TIME_STEP: f32 = (1.0 / DEFAULT_TICK_RATE) as f32;
+/// Positions closer than this count as the same state.
+const POSITION_RESOLUTION: f32 = 2.0;
+/// Speeds closer than this count as the same state.
+const SPEED_RESOLUTION: f32 = 10.0;
+/// Ticks each input is held for, as no player changes their input on every single tick.
+const INPUT_TICKS: usize = 3;
+
+/// Which of a level's doors and zone texts the player can touch, as `Identifier(iid)`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LevelReachability {
+    pub level: String,
+    pub reachable: Vec<String>,
+    pub unreachable: Vec<String>,
+}
+
+/// What the player does with the jump key during one tick.
+#[derive(Clone, Copy)]
+enum JumpInput {
+    /// Leaves the key as it was, so a jump keeps rising to its full height.
+    Keep,
+    Press,
+    LetGo,
+}
+
+#[derive(Clone, Copy)]
+struct PlayerState {
+    position: Vec2,
+    horizontal_speed: f32,
+    vertical_speed: f32,
+    grounded: bool,
+    /// Seconds left of the coyote time.
+    coyote: f32,
+    /// Whether the player is going up from a jump that letting go can cut short.
+    rising: bool,
+}
+
+impl PlayerState {
+    fn key(&self) -> (i32, i32, i32, i32, bool, i32, bool) {
+        (
+            (self.position.x / POSITION_RESOLUTION).round() as i32,
+            (self.position.y / POSITION_RESOLUTION).round() as i32,
+            (self.horizontal_speed / SPEED_RESOLUTION).round() as i32,
+            (self.vertical_speed / SPEED_RESOLUTION).round() as i32,
+            self.grounded,
+            (self.coyote.max(0.0) / TIME_STEP).round() as i32,
+            self.rising,
+        )
+    }
+
+    /// Whether the jump input does anything, as one that does not only repeats `Keep`.
+    fn accepts(&self, jump: JumpInput) -> bool {
+        match jump {
+            JumpInput::Keep => true,
+            JumpInput::Press => self.grounded || self.coyote > TIME_STEP,
+            JumpInput::LetGo => self.rising && self.vertical_speed < 0.0,
+        }
+    }
+
+    fn bounds(&self) -> Rect {
+        Rect::from_center_size(self.position, Vec2::new(PLAYER_WIDTH, PLAYER_HEIGHT))
+    }
+}
+
+/// LDtk coordinates point down, so gravity increases `y` here.
+fn step(
+    mut state: PlayerState,
+    direction: f32,
+    jump: JumpInput,
+    tuning: &MovementTuning,
+    solids: &[Rect],
+) -> PlayerState {
+    state.coyote = if state.grounded {
+        tuning.coyote_time
+    } else {
+        state.coyote - TIME_STEP
+    };
+    match jump {
+        JumpInput::Press if state.grounded || state.coyote > 0.0 => {
+            state.vertical_speed = -JUMP_POWER;
+            state.coyote = 0.0;
+            state.rising = true;
+        }
+        _ if state.vertical_speed >= 0.0 => state.rising = false,
+        JumpInput::LetGo if state.rising => {
+            state.vertical_speed *= tuning.jump_cut;
+            state.rising = false;
+        }
+        _ => {}
+    }
+    state.horizontal_speed =
+        tuning.run_speed(state.horizontal_speed, direction, state.grounded, TIME_STEP);
+    state.vertical_speed -= GRAVITY * TIME_STEP;
+    let half_size = Vec2::new(PLAYER_WIDTH, PLAYER_HEIGHT) / 2.0;
+
+    let moving_right = state.horizontal_speed > 0.0;
+    state.position.x += state.horizontal_speed * TIME_STEP;
+    let mut bounds = state.bounds();
+    for solid in solids.iter() {
+        if overlaps(&bounds, solid) {
+            state.position.x = if moving_right {
+                solid.min.x - half_size.x
+            } else {
+                solid.max.x + half_size.x
+            };
+            state.horizontal_speed = 0.0;
+            bounds = state.bounds();
+        }
+    }
+
+    let falling = state.vertical_speed > 0.0;
+    state.position.y += state.vertical_speed * TIME_STEP;
+    state.grounded = false;
+    let mut bounds = state.bounds();
+    for solid in solids.iter() {
+        if overlaps(&bounds, solid) {
+            state.position.y = if falling {
+                solid.min.y - half_size.y
+            } else {
+                solid.max.y + half_size.y
+            };
+            state.vertical_speed = 0.0;
+            state.grounded = falling;
+            bounds = state.bounds();
+        }
+    }
+    state
+}
+
+/// Touching edges do not count. Written out by hand because the analysis calls it a lot.
+fn overlaps(first: &Rect, second: &Rect) -> bool {
+    first.min.x < second.max.x
+        && second.min.x < first.max.x
+        && first.min.y < second.max.y
+        && second.min.y < first.max.y
+}
+
+fn entity_bounds(entity_instance: &bevy_ecs_ldtk::EntityInstance) -> Rect {
+    let size = Vec2::new(entity_instance.width as f32, entity_instance.height as f32);
+    let top_left = entity_instance.px.as_vec2() - entity_instance.pivot * size;
+    Rect::from_corners(top_left, top_left + size)
+}
+
+/// Returns `None` for levels without a player to start from.
+pub fn analyze_level(level: &Level) -> Option<LevelReachability> {
+    let entity_instances = Vec::from_iter(
+        level
+            .layer_instances
+            .iter()
+            .flatten()
+            .flat_map(|layer| layer.entity_instances.iter()),
+    );
+    // Colliders match their entity definition, so the instance size is the collider size.
+    let solids = Vec::from_iter(
+        entity_instances
+            .iter()
+            .filter(|entity_instance| {
+                matches!(
+                    entity_instance.identifier.as_str(),
+                    "Ground" | "Box" | "LevelBorder"
+                )
+            })
+            .map(|entity_instance| entity_bounds(entity_instance)),
+    );
+    let targets = Vec::from_iter(
+        entity_instances
+            .iter()
+            .filter(|entity_instance| {
+                matches!(entity_instance.identifier.as_str(), "Door" | "ZoneText")
+            })
+            .map(|entity_instance| {
+                (
+                    format!("{}({})", entity_instance.identifier, entity_instance.iid),
+                    entity_bounds(entity_instance),
+                )
+            }),
+    );
+    let spawn = entity_instances
+        .iter()
+        .find(|entity_instance| entity_instance.identifier == "Player")?;
+
+    // Rapier pushes a player spawned inside the ground back on top of it.
+    let mut start = PlayerState {
+        position: entity_bounds(spawn).center(),
+        horizontal_speed: 0.0,
+        vertical_speed: 0.0,
+        grounded: false,
+        coyote: 0.0,
+        rising: false,
+    };
+    while let Some(solid) = solids.iter().find(|solid| overlaps(&start.bounds(), solid)) {
+        start.position.y = solid.min.y - PLAYER_HEIGHT / 2.0;
+    }
+
+    let tuning = MovementTuning::default();
+    let mut touched = vec![false; targets.len()];
+    let mut visited = HashSet::from_iter([start.key()]);
+    let mut pending = VecDeque::from([start]);
+    let touch = |touched: &mut [bool], state: &PlayerState| {
+        for (index, (_, bounds)) in targets.iter().enumerate() {
+            touched[index] |= overlaps(&state.bounds(), bounds);
+        }
+    };
+    touch(&mut touched, &start);
+    // There is nothing left to find once every target is touched.
+    while !touched.iter().all(|touched| *touched) {
+        let Some(state) = pending.pop_front() else {
+            break;
+        };
+        for direction in [-1.0, 0.0, 1.0] {
+            for jump in [JumpInput::Keep, JumpInput::Press, JumpInput::LetGo] {
+                if !state.accepts(jump) {
+                    continue;
+                }
+                let mut next = step(state, direction, jump, &tuning, &solids);
+                touch(&mut touched, &next);
+                for _ in 1..INPUT_TICKS {
+                    next = step(next, direction, JumpInput::Keep, &tuning, &solids);
+                    touch(&mut touched, &next);
+                }
+                if next.position.y > level.px_hei as f32 + PLAYER_HEIGHT {
+                    continue;
+                }
+                if visited.insert(next.key()) {
+                    pending.push_back(next);
+                }
+            }
+        }
+    }
+
+    let mut reachability = LevelReachability {
+        level: level.identifier.clone(),
+        ..default()
+    };
+    for ((name, _), touched) in targets.into_iter().zip(touched) {
+        if touched {
+            reachability.reachable.push(name);
+        } else {
+            reachability.unreachable.push(name);
+        }
+    }
+    Some(reachability)
+}
+
+pub fn analyze_project(project: &LdtkJson) -> Vec<LevelReachability> {
+    Vec::from_iter(project.levels.iter().filter_map(analyze_level))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_ecs_ldtk::{ldtk::LayerInstance, EntityInstance};
+
+    fn entity(identifier: &str, px: IVec2, size: IVec2) -> EntityInstance {
+        EntityInstance {
+            identifier: identifier.to_string(),
+            iid: identifier.to_lowercase(),
+            px,
+            width: size.x,
+            height: size.y,
+            ..default()
+        }
+    }
+
+    #[test]
+    fn test_doors_above_the_jump_height_are_unreachable() {
+        // Given
+        let mut low_door = entity("Door", IVec2::new(400, 160), IVec2::splat(64));
+        low_door.iid = "low".to_string();
+        let mut high_door = entity("Door", IVec2::new(400, 0), IVec2::splat(64));
+        high_door.iid = "high".to_string();
+        let level = Level {
+            identifier: "Level_0".to_string(),
+            px_wid: 640,
+            px_hei: 320,
+            layer_instances: Some(vec![LayerInstance {
+                entity_instances: vec![
+                    entity("Ground", IVec2::new(0, 256), IVec2::new(640, 64)),
+                    entity("Player", IVec2::new(32, 190), IVec2::new(48, 66)),
+                    low_door,
+                    high_door,
+                ],
+                ..default()
+            }]),
+            ..default()
+        };
+
+        // When
+        let reachability = analyze_level(&level).unwrap();
+
+        // Then
+        assert_eq!(reachability.reachable, vec!["Door(low)"]);
+        assert_eq!(reachability.unreachable, vec!["Door(high)"]);
+    }
+
+    #[test]
+    fn test_every_door_and_zone_text_of_the_shipped_map_is_reachable() {
+        // Given
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/assets/map.ldtk");
+        let project: LdtkJson =
+            serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+
+        // When
+        let reachabilities = analyze_project(&project);
+
+        // Then
+        assert_eq!(reachabilities.len(), project.levels.len());
+        for reachability in reachabilities {
+            assert_eq!(
+                reachability.unreachable,
+                Vec::<String>::new(),
+                "{} has unreachable entities",
+                reachability.level
+            );
+        }
+    }
+}
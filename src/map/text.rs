@@ -1,4 +1,4 @@
-use super::ColliderBundle;
+use super::{ColliderBundle, EntityError, TryFromEntity};
 use bevy::{prelude::*, text::TextBounds, utils::HashMap};
 use bevy_ecs_ldtk::prelude::*;
 use bevy_rapier2d::prelude::*;
@@ -15,7 +15,7 @@ pub struct TextSignBundle {
 }
 
 #[derive(Default, Bundle)]
-struct TextSign {
+pub(super) struct TextSign {
     text: Text2d,
     text_font: TextFont,
     text_bounds: TextBounds,
@@ -26,6 +26,13 @@ struct TextSign {
 
 impl From<&EntityInstance> for TextSign {
     fn from(entity_instance: &EntityInstance) -> Self {
+        // `invalid_entity_system` reports the error and puts a placeholder in its place.
+        Self::try_from_entity(entity_instance).unwrap_or_default()
+    }
+}
+
+impl TryFromEntity for TextSign {
+    fn try_from_entity(entity_instance: &EntityInstance) -> Result<Self, EntityError> {
         let fields = HashMap::from_iter(entity_instance.field_instances.iter().map(|field| {
             (
                 field.identifier.clone(),
@@ -36,11 +43,14 @@ impl From<&EntityInstance> for TextSign {
                 },
             )
         }));
-        Self {
+        let font_size = fields
+            .get("font_size")
+            .ok_or(EntityError::MissingField("font_size"))?;
+        Ok(Self {
             text: Text2d::new(
                 fields
                     .get("text")
-                    .expect("Text is expected for a text sign!"),
+                    .ok_or(EntityError::MissingField("text"))?,
             ),
             text_bounds: TextBounds::new(
                 entity_instance.width as f32,
@@ -58,14 +68,12 @@ impl From<&EntityInstance> for TextSign {
                             default()
                         }
                     })
-                    .expect("Default color is expected to be set by the editor!"),
+                    .ok_or(EntityError::MissingField("color"))?,
             ),
             text_font: TextFont {
-                font_size: fields
-                    .get("font_size")
-                    .expect("The font size of the text is expected!")
-                    .parse()
-                    .expect("Font size is expected to be a number!"),
+                font_size: font_size.parse().map_err(|_| {
+                    EntityError::invalid("font_size", format!("{font_size:?} is not a number"))
+                })?,
                 ..default()
             },
             text_layout: TextLayout {
@@ -73,7 +81,7 @@ impl From<&EntityInstance> for TextSign {
                 ..default()
             },
             ..default()
-        }
+        })
     }
 }
 
@@ -114,6 +122,13 @@ pub struct TextInfo {
 
 impl From<&EntityInstance> for TextInfo {
     fn from(entity_instance: &EntityInstance) -> Self {
+        // `invalid_entity_system` reports the error and puts a placeholder in its place.
+        Self::try_from_entity(entity_instance).unwrap_or_default()
+    }
+}
+
+impl TryFromEntity for TextInfo {
+    fn try_from_entity(entity_instance: &EntityInstance) -> Result<Self, EntityError> {
         let fields = HashMap::from_iter(entity_instance.field_instances.iter().map(|field| {
             (
                 field.identifier.clone(),
@@ -123,12 +138,12 @@ impl From<&EntityInstance> for TextInfo {
                 },
             )
         }));
-        Self {
+        Ok(Self {
             text: fields
                 .get("text")
-                .expect("Text to be displayed is expected!")
+                .ok_or(EntityError::MissingField("text"))?
                 .clone(),
-        }
+        })
     }
 }
 
@@ -218,3 +233,11 @@ pub fn hide_zone_text_system(
         text_content.0 = "".to_string();
     }
 }
+
+pub fn hide_zone_text_on_exit_system(
+    mut text_container_info: Query<&mut Visibility, With<CommonTextContainer>>,
+) {
+    for mut container_visibility in text_container_info.iter_mut() {
+        *container_visibility = Visibility::Hidden;
+    }
+}
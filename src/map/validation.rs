@@ -0,0 +1,276 @@
+use super::text::{TextInfo, TextSign};
+use crate::riddles::RiddleInfo;
+use bevy::utils::HashSet;
+use bevy_ecs_ldtk::{ldtk::LdtkJson, prelude::*};
+use std::{error::Error, fmt};
+
+/// Why an LDtk entity cannot become the components the game registers for it.
+#[derive(Clone, Debug, PartialEq)]
+pub enum EntityError {
+    MissingField(&'static str),
+    InvalidField { field: &'static str, reason: String },
+}
+
+impl EntityError {
+    pub fn invalid(field: &'static str, reason: impl Into<String>) -> Self {
+        EntityError::InvalidField {
+            field,
+            reason: reason.into(),
+        }
+    }
+}
+
+impl fmt::Display for EntityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EntityError::MissingField(field) => write!(f, "missing required field {field}"),
+            EntityError::InvalidField { field, reason } => {
+                write!(f, "invalid field {field}: {reason}")
+            }
+        }
+    }
+}
+
+impl Error for EntityError {}
+
+/// The fallible counterpart of the `From<&EntityInstance>` conversions `LdtkEntity` relies on.
+pub trait TryFromEntity: Sized {
+    fn try_from_entity(entity_instance: &EntityInstance) -> Result<Self, EntityError>;
+}
+
+/// One problem in a map file, reported as `file:level:entity: error`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MapProblem {
+    pub file: String,
+    pub level: String,
+    pub entity: String,
+    pub error: EntityError,
+}
+
+impl fmt::Display for MapProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}: {}",
+            self.file, self.level, self.entity, self.error
+        )
+    }
+}
+
+/// Checks an entity the way `MapPlugin` and `PlayerPlugin` would spawn it.
+pub(super) fn validate_entity(
+    entity_instance: &EntityInstance,
+    level_iids: &HashSet<&str>,
+) -> Result<(), EntityError> {
+    check_entity(entity_instance)?;
+    if entity_instance.identifier == "Door" {
+        let riddle_info = RiddleInfo::try_from_entity(entity_instance)?;
+        if !level_iids.contains(riddle_info.next_level()) {
+            return Err(EntityError::invalid(
+                "next_level",
+                format!("{} is not the IID of a level", riddle_info.next_level()),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Checks the fields of an entity on its own, without looking at the rest of the project.
+pub(super) fn check_entity(entity_instance: &EntityInstance) -> Result<(), EntityError> {
+    match entity_instance.identifier.as_str() {
+        "Door" => {
+            RiddleInfo::try_from_entity(entity_instance)?;
+        }
+        "TextSign" => {
+            TextSign::try_from_entity(entity_instance)?;
+        }
+        "ZoneText" => {
+            TextInfo::try_from_entity(entity_instance)?;
+        }
+        "Ground" | "LevelBorder" | "Box" | "Player" => {}
+        identifier => {
+            return Err(EntityError::invalid(
+                "__identifier",
+                format!("no plugin registers the entity {identifier}"),
+            ))
+        }
+    }
+    Ok(())
+}
+
+pub fn validate_project(file: &str, project: &LdtkJson) -> Vec<MapProblem> {
+    let level_iids = HashSet::from_iter(project.levels.iter().map(|level| level.iid.as_str()));
+    let mut problems = Vec::new();
+    for level in project.levels.iter() {
+        for entity_instance in level
+            .layer_instances
+            .iter()
+            .flatten()
+            .flat_map(|layer| layer.entity_instances.iter())
+        {
+            if let Err(error) = validate_entity(entity_instance, &level_iids) {
+                problems.push(MapProblem {
+                    file: file.to_string(),
+                    level: level.identifier.clone(),
+                    entity: format!("{}({})", entity_instance.identifier, entity_instance.iid),
+                    error,
+                });
+            }
+        }
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::prelude::default;
+    use bevy_ecs_ldtk::ldtk::{FieldInstance, Level};
+
+    fn field(identifier: &str, value: FieldValue) -> FieldInstance {
+        FieldInstance {
+            identifier: identifier.to_string(),
+            tile: None,
+            field_instance_type: String::new(),
+            value,
+            def_uid: 0,
+            real_editor_values: Vec::new(),
+        }
+    }
+
+    fn level(identifier: &str, iid: &str, entity_instances: Vec<EntityInstance>) -> Level {
+        Level {
+            identifier: identifier.to_string(),
+            iid: iid.to_string(),
+            layer_instances: Some(vec![LayerInstance {
+                entity_instances,
+                ..default()
+            }]),
+            ..default()
+        }
+    }
+
+    fn door(iid: &str, fields: Vec<FieldInstance>) -> EntityInstance {
+        EntityInstance {
+            identifier: "Door".to_string(),
+            iid: iid.to_string(),
+            field_instances: fields,
+            ..default()
+        }
+    }
+
+    #[test]
+    fn test_validate_project_reports_every_broken_entity() {
+        // Given
+        let text_sign = EntityInstance {
+            identifier: "TextSign".to_string(),
+            iid: "sign".to_string(),
+            field_instances: vec![
+                field("text", FieldValue::String(Some("Hello".to_string()))),
+                field("font_size", FieldValue::Float(None)),
+                field("color", FieldValue::Color(default())),
+            ],
+            ..default()
+        };
+        let project = LdtkJson {
+            levels: vec![
+                level(
+                    "Level_0",
+                    "first",
+                    vec![
+                        door(
+                            "unknown-level",
+                            vec![
+                                field("question", FieldValue::String(Some("2 + 2?".into()))),
+                                field("answer", FieldValue::String(Some("4".into()))),
+                                field("next_level", FieldValue::String(Some("third".into()))),
+                            ],
+                        ),
+                        door(
+                            "no-question",
+                            vec![
+                                field("answer", FieldValue::String(Some("4".into()))),
+                                field("next_level", FieldValue::String(Some("second".into()))),
+                            ],
+                        ),
+                    ],
+                ),
+                level(
+                    "Level_1",
+                    "second",
+                    vec![
+                        text_sign,
+                        door(
+                            "valid",
+                            vec![
+                                field("question", FieldValue::String(Some("2 + 2?".into()))),
+                                field("answer", FieldValue::String(Some("4".into()))),
+                                field("next_level", FieldValue::String(Some("first".into()))),
+                            ],
+                        ),
+                    ],
+                ),
+            ],
+            ..default()
+        };
+
+        // When
+        let problems = validate_project("map.ldtk", &project);
+
+        // Then
+        let problems = Vec::from_iter(problems.iter().map(ToString::to_string));
+        assert_eq!(
+            problems,
+            vec![
+                "map.ldtk:Level_0:Door(unknown-level): invalid field next_level: third is not the IID of a level",
+                "map.ldtk:Level_0:Door(no-question): missing required field question",
+                "map.ldtk:Level_1:TextSign(sign): invalid field font_size: \"\" is not a number",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_project_rejects_a_negative_hint_delay() {
+        // Given
+        let project = LdtkJson {
+            levels: vec![level(
+                "Level_0",
+                "first",
+                vec![door(
+                    "impatient",
+                    vec![
+                        field("question", FieldValue::String(Some("2 + 2?".into()))),
+                        field("answer", FieldValue::String(Some("4".into()))),
+                        field("next_level", FieldValue::String(Some("first".into()))),
+                        field("hint_delay", FieldValue::Float(Some(-5.0))),
+                    ],
+                )],
+            )],
+            ..default()
+        };
+
+        // When
+        let problems = validate_project("map.ldtk", &project);
+
+        // Then
+        let problems = Vec::from_iter(problems.iter().map(ToString::to_string));
+        assert_eq!(
+            problems,
+            vec!["map.ldtk:Level_0:Door(impatient): invalid field hint_delay: -5 is not a number of seconds"]
+        );
+    }
+
+    #[test]
+    fn test_shipped_map_is_valid() {
+        // Given
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/assets/map.ldtk");
+        let project: LdtkJson =
+            serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+
+        // When
+        let problems = validate_project(path, &project);
+
+        // Then
+        assert_eq!(problems, Vec::new());
+    }
+}
@@ -0,0 +1,331 @@
+use crate::{
+    actions::{Action, ActionState},
+    save::SaveSlots,
+    GameState,
+};
+use bevy::{prelude::*, window::PrimaryWindow};
+use bevy_rapier2d::prelude::*;
+
+pub mod nodes;
+
+pub struct MenuPlugin;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SettingsReturnState(GameState::MainMenu))
+            .add_systems(OnEnter(GameState::MainMenu), main_menu_setup_system)
+            .add_systems(OnEnter(GameState::Paused), pause_menu_setup_system)
+            .add_systems(OnExit(GameState::MainMenu), menu_cleanup_system)
+            .add_systems(OnExit(GameState::Paused), menu_cleanup_system)
+            .add_systems(
+                Update,
+                (
+                    pause_system.run_if(in_state(GameState::MapExploring)),
+                    resume_system.run_if(in_state(GameState::Paused)),
+                    leave_settings_system.run_if(in_state(GameState::Settings)),
+                    menu_button_system
+                        .run_if(in_state(GameState::MainMenu).or(in_state(GameState::Paused))),
+                    button_color_system,
+                    menu_state_system.run_if(state_changed::<GameState>),
+                ),
+            );
+    }
+}
+
+/// The state the settings screen returns to, since it is reachable from several menus.
+#[derive(Resource)]
+pub struct SettingsReturnState(pub GameState);
+
+#[derive(Component)]
+struct MenuScreen;
+
+#[derive(Clone, Copy, Component)]
+enum MenuButton {
+    NewGame,
+    Continue,
+    Settings,
+    Quit,
+    Resume,
+    MainMenu,
+}
+
+fn spawn_menu(
+    commands: &mut Commands,
+    asset_server: &Res<AssetServer>,
+    title: &str,
+    buttons: &[(MenuButton, &str)],
+) {
+    use nodes::*;
+
+    commands
+        .spawn(screen_root())
+        .insert(MenuScreen)
+        .with_children(|parent| {
+            parent.spawn(title_text(asset_server, title));
+            for (action, label) in buttons {
+                parent
+                    .spawn(button())
+                    .insert(*action)
+                    .with_children(|parent| {
+                        parent.spawn(button_text(asset_server, label));
+                    });
+            }
+        });
+}
+
+fn main_menu_setup_system(mut commands: Commands, asset_server: Res<AssetServer>) {
+    spawn_menu(
+        &mut commands,
+        &asset_server,
+        "Puzzle Up",
+        &[
+            (MenuButton::NewGame, "New Game"),
+            (MenuButton::Continue, "Continue"),
+            (MenuButton::Settings, "Settings"),
+            (MenuButton::Quit, "Quit"),
+        ],
+    );
+}
+
+fn pause_menu_setup_system(mut commands: Commands, asset_server: Res<AssetServer>) {
+    spawn_menu(
+        &mut commands,
+        &asset_server,
+        "Paused",
+        &[
+            (MenuButton::Resume, "Resume"),
+            (MenuButton::Settings, "Settings"),
+            (MenuButton::MainMenu, "Main Menu"),
+            (MenuButton::Quit, "Quit"),
+        ],
+    );
+}
+
+fn menu_cleanup_system(mut commands: Commands, screens: Query<Entity, With<MenuScreen>>) {
+    for screen in screens.iter() {
+        commands.entity(screen).despawn_recursive();
+    }
+}
+
+fn menu_button_system(
+    mut commands: Commands,
+    save_slots: Res<SaveSlots>,
+    current_state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut settings_return_state: ResMut<SettingsReturnState>,
+    mut exit: EventWriter<AppExit>,
+    buttons: Query<(&Interaction, &MenuButton), Changed<Interaction>>,
+) {
+    for (interaction, action) in buttons.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        match action {
+            MenuButton::NewGame => {
+                crate::save::new_game(&mut commands, &save_slots);
+                next_state.set(GameState::LevelLoading);
+            }
+            MenuButton::Continue => next_state.set(GameState::SlotSelecting),
+            MenuButton::Settings => {
+                settings_return_state.0 = current_state.get().clone();
+                next_state.set(GameState::Settings);
+            }
+            MenuButton::Quit => {
+                exit.send(AppExit::Success);
+            }
+            MenuButton::Resume => next_state.set(GameState::MapExploring),
+            MenuButton::MainMenu => next_state.set(GameState::MainMenu),
+        }
+        return;
+    }
+}
+
+fn pause_system(action_state: Res<ActionState>, mut next_state: ResMut<NextState<GameState>>) {
+    if action_state.just_pressed(Action::Cancel) {
+        next_state.set(GameState::Paused);
+    }
+}
+
+fn resume_system(action_state: Res<ActionState>, mut next_state: ResMut<NextState<GameState>>) {
+    if action_state.just_pressed(Action::Cancel) {
+        next_state.set(GameState::MapExploring);
+    }
+}
+
+fn leave_settings_system(
+    action_state: Res<ActionState>,
+    settings_return_state: Res<SettingsReturnState>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if action_state.just_pressed(Action::Cancel) {
+        next_state.set(settings_return_state.0.clone());
+    }
+}
+
+/// Only buttons; answer slots are clickable too, and every UI node has a background color.
+type ChangedButtons = (Changed<Interaction>, With<Button>);
+
+fn button_color_system(mut buttons: Query<(&Interaction, &mut BackgroundColor), ChangedButtons>) {
+    use nodes::*;
+
+    for (interaction, mut color) in buttons.iter_mut() {
+        color.0 = match interaction {
+            Interaction::Pressed => PRESSED_BUTTON_COLOR,
+            Interaction::Hovered => HOVERED_BUTTON_COLOR,
+            Interaction::None => BUTTON_COLOR,
+        };
+    }
+}
+
+/// Menus and the riddle keypad need the cursor, and nothing may move behind a menu.
+fn menu_state_system(
+    state: Res<State<GameState>>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+    mut rapier_config: Query<&mut RapierConfiguration>,
+) {
+    let in_menu = matches!(
+        state.get(),
+        GameState::MainMenu
+            | GameState::SlotSelecting
+            | GameState::Paused
+            | GameState::Settings
+            | GameState::Controls
+    );
+    for mut window in windows.iter_mut() {
+        window.cursor_options.visible = in_menu || *state.get() == GameState::RiddleSolving;
+    }
+    for mut rapier_config in rapier_config.iter_mut() {
+        rapier_config.physics_pipeline_active = !in_menu;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actions::{update_action_state_system, InputMap};
+    use bevy::state::app::StatesPlugin;
+
+    fn menu_app(state: GameState) -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatesPlugin))
+            .insert_state(state)
+            .init_resource::<ButtonInput<KeyCode>>()
+            .init_resource::<InputMap>()
+            .init_resource::<ActionState>()
+            .add_systems(PreUpdate, update_action_state_system)
+            .add_systems(
+                Update,
+                (
+                    pause_system.run_if(in_state(GameState::MapExploring)),
+                    resume_system.run_if(in_state(GameState::Paused)),
+                    menu_state_system.run_if(state_changed::<GameState>),
+                ),
+            );
+        app
+    }
+
+    fn press_escape(app: &mut App) {
+        let mut keyboard_input = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+        keyboard_input.release(KeyCode::Escape);
+        keyboard_input.clear();
+        keyboard_input.press(KeyCode::Escape);
+        app.update();
+        app.world_mut()
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .clear();
+        app.update();
+    }
+
+    #[test]
+    fn test_escape_pauses_and_stops_physics() {
+        // Given
+        let mut app = menu_app(GameState::MapExploring);
+        let rapier_configuration = app.world_mut().spawn(RapierConfiguration::new(100.0)).id();
+        let window = app
+            .world_mut()
+            .spawn((Window::default(), PrimaryWindow))
+            .id();
+
+        // When
+        press_escape(&mut app);
+
+        // Then
+        assert_eq!(
+            app.world().resource::<State<GameState>>().get(),
+            &GameState::Paused
+        );
+        let rapier_configuration = app
+            .world()
+            .get::<RapierConfiguration>(rapier_configuration)
+            .unwrap();
+        assert!(!rapier_configuration.physics_pipeline_active);
+        assert!(
+            app.world()
+                .get::<Window>(window)
+                .unwrap()
+                .cursor_options
+                .visible
+        );
+    }
+
+    #[test]
+    fn test_escape_resumes_and_restarts_physics() {
+        // Given
+        let mut app = menu_app(GameState::Paused);
+        let rapier_configuration = app.world_mut().spawn(RapierConfiguration::new(100.0)).id();
+        let window = app
+            .world_mut()
+            .spawn((Window::default(), PrimaryWindow))
+            .id();
+        app.update();
+
+        // When
+        press_escape(&mut app);
+
+        // Then
+        assert_eq!(
+            app.world().resource::<State<GameState>>().get(),
+            &GameState::MapExploring
+        );
+        let rapier_configuration = app
+            .world()
+            .get::<RapierConfiguration>(rapier_configuration)
+            .unwrap();
+        assert!(rapier_configuration.physics_pipeline_active);
+        assert!(
+            !app.world()
+                .get::<Window>(window)
+                .unwrap()
+                .cursor_options
+                .visible
+        );
+    }
+
+    #[test]
+    fn test_button_color_system_leaves_clickable_non_buttons_alone() {
+        // Given
+        let mut app = App::new();
+
+        app.add_systems(Update, button_color_system);
+        let button = app
+            .world_mut()
+            .spawn((
+                Button,
+                Interaction::Hovered,
+                BackgroundColor(nodes::BUTTON_COLOR),
+            ))
+            .id();
+        let slot = app
+            .world_mut()
+            .spawn((Interaction::Hovered, BackgroundColor(Color::BLACK)))
+            .id();
+
+        // When
+        app.update();
+
+        // Then
+        let color = |entity| app.world().get::<BackgroundColor>(entity).unwrap().0;
+        assert_eq!(color(button), nodes::HOVERED_BUTTON_COLOR);
+        assert_eq!(color(slot), Color::BLACK);
+    }
+}
@@ -0,0 +1,96 @@
+use bevy::prelude::*;
+
+pub const BUTTON_COLOR: Color = Color::srgb(0.35, 0.35, 0.7);
+pub const HOVERED_BUTTON_COLOR: Color = Color::srgb(0.45, 0.45, 0.8);
+pub const PRESSED_BUTTON_COLOR: Color = Color::srgb(0.25, 0.25, 0.55);
+
+pub fn screen_root() -> (Node, BackgroundColor) {
+    (
+        Node {
+            display: Display::Flex,
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            row_gap: Val::Px(20.0),
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            padding: UiRect::all(Val::Percent(4.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgb(0.5, 0.5, 0.85)),
+    )
+}
+
+pub fn title_text(asset_server: &Res<AssetServer>, title: &str) -> (Text, TextColor, TextFont) {
+    (
+        Text(title.to_string()),
+        TextColor(Color::WHITE),
+        TextFont {
+            font: asset_server.load("fonts/MontserratAlternates-MediumItalic.ttf"),
+            font_size: 60.0,
+            ..default()
+        },
+    )
+}
+
+pub fn button_row() -> Node {
+    Node {
+        display: Display::Flex,
+        column_gap: Val::Px(20.0),
+        ..default()
+    }
+}
+
+pub fn slot_row() -> Node {
+    Node {
+        display: Display::Flex,
+        align_items: AlignItems::Center,
+        column_gap: Val::Px(20.0),
+        width: Val::Percent(80.0),
+        ..default()
+    }
+}
+
+pub fn slot_summary(
+    asset_server: &Res<AssetServer>,
+    name: &str,
+    summary: &str,
+) -> (Node, Text, TextColor, TextFont) {
+    (
+        Node {
+            flex_grow: 1.0,
+            ..default()
+        },
+        Text(format!("{name}\n{summary}")),
+        TextColor(Color::WHITE),
+        TextFont {
+            font: asset_server.load("fonts/MontserratAlternates-MediumItalic.ttf"),
+            font_size: 25.0,
+            ..default()
+        },
+    )
+}
+
+pub fn button() -> (Button, Node, BackgroundColor) {
+    (
+        Button,
+        Node {
+            padding: UiRect::axes(Val::Px(20.0), Val::Px(10.0)),
+            justify_content: JustifyContent::Center,
+            min_width: Val::Px(150.0),
+            ..default()
+        },
+        BackgroundColor(BUTTON_COLOR),
+    )
+}
+
+pub fn button_text(asset_server: &Res<AssetServer>, label: &str) -> (Text, TextColor, TextFont) {
+    (
+        Text(label.to_string()),
+        TextColor(Color::WHITE),
+        TextFont {
+            font: asset_server.load("fonts/MontserratAlternates-MediumItalic.ttf"),
+            font_size: 25.0,
+            ..default()
+        },
+    )
+}
@@ -0,0 +1,200 @@
+use bevy::{prelude::*, transform::TransformSystem};
+use bevy_rapier2d::prelude::*;
+use std::time::Duration;
+
+/// Runs Rapier on a fixed timestep, so jumps and runs do not depend on the frame rate.
+///
+/// Bodies with an `Interpolated` component are drawn between their last two physics positions.
+pub struct PhysicsPlugin;
+
+impl Plugin for PhysicsPlugin {
+    fn build(&self, app: &mut App) {
+        // Rapier warns about any other timestep mode while it builds in `FixedUpdate`.
+        app.insert_resource(timestep_mode(DEFAULT_TICK_RATE))
+            .insert_resource(Time::<Fixed>::from_hz(DEFAULT_TICK_RATE))
+            .insert_resource(TickRate(DEFAULT_TICK_RATE))
+            .add_plugins(
+                RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(100.0).in_fixed_schedule(),
+            )
+            .add_systems(
+                PreUpdate,
+                apply_tick_rate_system.run_if(resource_changed::<TickRate>),
+            )
+            .add_systems(FixedFirst, restore_physics_transform_system)
+            .add_systems(FixedPostUpdate, record_physics_transform_system)
+            .add_systems(
+                PostUpdate,
+                interpolate_transform_system.before(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+/// Physics ticks per second unless the settings say otherwise.
+pub const DEFAULT_TICK_RATE: f64 = 60.0;
+pub const MIN_TICK_RATE: f64 = 30.0;
+pub const MAX_TICK_RATE: f64 = 240.0;
+
+/// Physics ticks per second.
+#[derive(Clone, Copy, Debug, PartialEq, Resource)]
+pub struct TickRate(pub f64);
+
+impl TickRate {
+    /// The ticks per second actually run, within the supported range.
+    pub fn clamped(&self) -> f64 {
+        self.0.clamp(MIN_TICK_RATE, MAX_TICK_RATE)
+    }
+
+    /// How long one tick lasts.
+    pub fn tick(&self) -> Duration {
+        Duration::from_secs_f64(1.0 / self.clamped())
+    }
+}
+
+/// Draws a body between its last two physics positions instead of snapping to the latest one.
+#[derive(Default, Component)]
+pub struct Interpolated {
+    previous: Option<Vec3>,
+    current: Option<Vec3>,
+    rendered: Option<Vec3>,
+}
+
+fn timestep_mode(tick_rate: f64) -> TimestepMode {
+    TimestepMode::Fixed {
+        dt: (1.0 / tick_rate) as f32,
+        substeps: 1,
+    }
+}
+
+fn apply_tick_rate_system(
+    tick_rate: Res<TickRate>,
+    mut fixed_time: ResMut<Time<Fixed>>,
+    mut timestep: ResMut<TimestepMode>,
+) {
+    fixed_time.set_timestep(tick_rate.tick());
+    *timestep = timestep_mode(tick_rate.clamped());
+}
+
+/// Puts bodies back where physics left them, unless something else moved them in between.
+fn restore_physics_transform_system(mut bodies: Query<(&mut Transform, &mut Interpolated)>) {
+    for (mut transform, mut interpolated) in bodies.iter_mut() {
+        let rendered = interpolated.rendered.take();
+        match interpolated.current {
+            Some(current) if rendered == Some(transform.translation) => {
+                transform.translation = current
+            }
+            Some(current) if current == transform.translation => {}
+            _ => {
+                interpolated.previous = None;
+                interpolated.current = None;
+            }
+        }
+    }
+}
+
+fn record_physics_transform_system(mut bodies: Query<(&Transform, &mut Interpolated)>) {
+    for (transform, mut interpolated) in bodies.iter_mut() {
+        interpolated.previous = Some(interpolated.current.unwrap_or(transform.translation));
+        interpolated.current = Some(transform.translation);
+    }
+}
+
+fn interpolate_transform_system(
+    fixed_time: Res<Time<Fixed>>,
+    mut bodies: Query<(&mut Transform, &mut Interpolated)>,
+) {
+    for (mut transform, mut interpolated) in bodies.iter_mut() {
+        let (Some(previous), Some(current)) = (interpolated.previous, interpolated.current) else {
+            continue;
+        };
+        // Anything but the latest physics or drawn position means the body was moved on purpose.
+        if transform.translation != current && interpolated.rendered != Some(transform.translation)
+        {
+            continue;
+        }
+        let rendered = previous.lerp(current, fixed_time.overstep_fraction());
+        transform.translation = rendered;
+        interpolated.rendered = Some(rendered);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::time::TimeUpdateStrategy;
+
+    /// Returns the last two physics positions and the drawn one.
+    fn fall_for_half_a_second(frame_rate: u32) -> (Vec3, Vec3, Vec3) {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, PhysicsPlugin))
+            .insert_resource(TimeUpdateStrategy::ManualDuration(
+                Duration::from_secs(1) / frame_rate,
+            ));
+        let body = app
+            .world_mut()
+            .spawn((
+                Collider::cuboid(10.0, 10.0),
+                Transform::default(),
+                RigidBody::Dynamic,
+                Interpolated::default(),
+            ))
+            .id();
+        while app.world().resource::<Time<Fixed>>().elapsed_secs() < 0.5 {
+            app.update();
+        }
+        let interpolated = app.world().get::<Interpolated>(body).unwrap();
+        (
+            interpolated.previous.unwrap(),
+            interpolated.current.unwrap(),
+            app.world().get::<Transform>(body).unwrap().translation,
+        )
+    }
+
+    #[test]
+    fn test_physics_does_not_depend_on_the_frame_rate() {
+        // Given
+        let frame_rates = [60, 144];
+
+        // When
+        let [(previous_at_60, current_at_60, drawn_at_60), (previous, current, drawn)] =
+            frame_rates.map(fall_for_half_a_second);
+
+        // Then
+        assert!(current.y < -10.0);
+        assert_eq!((previous_at_60, current_at_60), (previous, current));
+        assert!(drawn_at_60.abs_diff_eq(current, 0.001));
+        assert!(current.y <= drawn.y && drawn.y <= previous.y);
+    }
+
+    #[test]
+    fn test_moved_bodies_are_not_pulled_back_to_their_physics_position() {
+        // Given
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, PhysicsPlugin))
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(7)));
+        let body = app
+            .world_mut()
+            .spawn((
+                Collider::cuboid(10.0, 10.0),
+                Transform::default(),
+                RigidBody::Fixed,
+                Interpolated::default(),
+            ))
+            .id();
+        for _ in 0..10 {
+            app.update();
+        }
+
+        // When
+        app.world_mut()
+            .get_mut::<Transform>(body)
+            .unwrap()
+            .translation = Vec3::new(100.0, 50.0, 0.0);
+        for _ in 0..10 {
+            app.update();
+        }
+
+        // Then
+        let transform = app.world().get::<Transform>(body).unwrap();
+        assert_eq!(transform.translation, Vec3::new(100.0, 50.0, 0.0));
+    }
+}
@@ -1,4 +1,9 @@
-use crate::{map::Ground, GameState};
+use crate::{
+    actions::{Action, ActionState},
+    map::Ground,
+    physics::Interpolated,
+    GameState,
+};
 use animations::{AnimationInfo, AnimationType, AnimationsPlugin};
 use bevy::{prelude::*, utils::HashMap};
 use bevy_ecs_ldtk::prelude::*;
@@ -12,21 +17,91 @@ impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(AnimationsPlugin)
             .add_systems(
-                Update,
-                player_movement_system.run_if(in_state(GameState::MapExploring)),
+                FixedUpdate,
+                (
+                    player_movement_system.before(PhysicsSet::SyncBackend),
+                    grounded_system.after(PhysicsSet::Writeback),
+                )
+                    .run_if(in_state(GameState::MapExploring)),
             )
+            .init_resource::<MovementTuning>()
             .register_ldtk_entity::<PlayerBundle>("Player");
     }
 }
 
-const PLAYER_WIDTH: f32 = 60.0;
-const PLAYER_HEIGHT: f32 = 110.0;
-const JUMP_POWER: f32 = 250.0;
-const RUN_POWER: f32 = 100.0;
+pub(crate) const PLAYER_WIDTH: f32 = 60.0;
+pub(crate) const PLAYER_HEIGHT: f32 = 110.0;
+pub(crate) const JUMP_POWER: f32 = 250.0;
+pub(crate) const RUN_POWER: f32 = 100.0;
+/// How far below the player's feet ground still counts as standing on it.
+const GROUND_PROBE_DISTANCE: f32 = 2.0;
+const GROUND_PROBE_HEIGHT: f32 = 2.0;
+/// Keeps the probe off walls the player only leans against.
+const GROUND_PROBE_INSET: f32 = 1.0;
 
 #[derive(Default, Component)]
 pub struct Player;
 
+/// Whether the player stands on ground, as of the last physics step.
+#[derive(Default, Component, PartialEq)]
+pub struct Grounded(pub bool);
+
+/// How the player picks up speed and how forgiving jumping is.
+#[derive(Clone, Debug, Resource)]
+pub struct MovementTuning {
+    /// How long after walking off a ledge the player can still jump, in seconds.
+    pub coyote_time: f32,
+    /// How long a jump pressed in the air is remembered for the landing, in seconds.
+    pub jump_buffer_time: f32,
+    /// What is left of the upward speed when the jump key is let go early.
+    pub jump_cut: f32,
+    /// Horizontal speed changes, in pixels per second squared.
+    pub ground_acceleration: f32,
+    pub ground_deceleration: f32,
+    pub air_acceleration: f32,
+    pub air_deceleration: f32,
+}
+
+impl Default for MovementTuning {
+    fn default() -> Self {
+        Self {
+            coyote_time: 0.1,
+            jump_buffer_time: 0.1,
+            jump_cut: 0.5,
+            ground_acceleration: 1000.0,
+            ground_deceleration: 1500.0,
+            air_acceleration: 600.0,
+            air_deceleration: 300.0,
+        }
+    }
+}
+
+impl MovementTuning {
+    /// Moves the horizontal speed towards running in `direction`, or towards standing still.
+    pub(crate) fn run_speed(&self, speed: f32, direction: f32, grounded: bool, delta: f32) -> f32 {
+        let target = direction * RUN_POWER;
+        let rate = match (grounded, target != 0.0) {
+            (true, true) => self.ground_acceleration,
+            (true, false) => self.ground_deceleration,
+            (false, true) => self.air_acceleration,
+            (false, false) => self.air_deceleration,
+        };
+        speed + (target - speed).clamp(-rate * delta, rate * delta)
+    }
+}
+
+/// Seconds left of the coyote time and of the buffered jump.
+#[derive(Default, Component)]
+pub struct JumpWindows {
+    coyote: f32,
+    buffer: f32,
+    jump_held: bool,
+    /// Whether the player is still going up from a jump that letting go can cut short.
+    rising: bool,
+    /// Whether the player jumped and is still on the ground it took off from.
+    taking_off: bool,
+}
+
 #[derive(Default, Bundle, LdtkEntity)]
 struct PlayerBundle {
     #[sprite_sheet("player/player_tilesheet.png", 80, 110, 9, 3, 0, 0, 24)]
@@ -36,6 +111,9 @@ struct PlayerBundle {
     #[bundle()]
     collider_bundle: ColliderBundle,
     velocity: Velocity,
+    interpolated: Interpolated,
+    grounded: Grounded,
+    jump_windows: JumpWindows,
     player: Player,
 }
 
@@ -74,50 +152,112 @@ impl From<&EntityInstance> for ColliderBundle {
     }
 }
 
-fn player_movement_system(
-    keyboard_input: Res<ButtonInput<KeyCode>>,
+/// Casts a thin slice of the player's feet downwards, so seams and corners between ground tiles
+/// count as ground too.
+fn grounded_system(
     rapier_context: Query<&RapierContext>,
-    mut player_info: Query<(Entity, &mut Velocity, &mut Sprite), With<Player>>,
-    tile_info: Query<Entity, With<Ground>>,
+    mut player_info: Query<(Entity, &Collider, &mut Grounded), With<Player>>,
+    ground_info: Query<(), With<Ground>>,
 ) {
-    let (player, mut velocity, mut sprite) = player_info.single_mut();
-    let up = keyboard_input.any_pressed([KeyCode::ArrowUp, KeyCode::KeyW]);
-    let left = keyboard_input.any_pressed([KeyCode::ArrowLeft, KeyCode::KeyA]);
-    let right = keyboard_input.any_pressed([KeyCode::ArrowRight, KeyCode::KeyD]);
+    let rapier_context = rapier_context.single();
+    for (player, collider, mut grounded) in player_info.iter_mut() {
+        let Some(body) = rapier_context
+            .entity2body()
+            .get(&player)
+            .and_then(|handle| rapier_context.bodies.get(*handle))
+        else {
+            continue;
+        };
+        let half_extents = collider
+            .as_cuboid()
+            .expect("The player collider is expected to be a cuboid!")
+            .half_extents();
+        let feet = Vec2::new(
+            body.translation().x,
+            body.translation().y - half_extents.y + GROUND_PROBE_HEIGHT / 2.0,
+        );
+        let probe = Collider::cuboid(
+            half_extents.x - GROUND_PROBE_INSET,
+            GROUND_PROBE_HEIGHT / 2.0,
+        );
+        let is_ground = |entity| ground_info.contains(entity);
+        let hit = rapier_context.cast_shape(
+            feet,
+            0.0,
+            Vec2::NEG_Y,
+            &probe,
+            ShapeCastOptions::with_max_time_of_impact(GROUND_PROBE_DISTANCE),
+            QueryFilter::new()
+                .exclude_rigid_body(player)
+                .exclude_sensors()
+                .predicate(&is_ground),
+        );
+        grounded.set_if_neq(Grounded(hit.is_some()));
+    }
+}
 
-    velocity.linvel.x = if left {
+fn player_movement_system(
+    time: Res<Time>,
+    action_state: Res<ActionState>,
+    tuning: Res<MovementTuning>,
+    mut player_info: Query<(&mut Velocity, &mut Sprite, &Grounded, &mut JumpWindows), With<Player>>,
+) {
+    let (mut velocity, mut sprite, grounded, mut jump_windows) = player_info.single_mut();
+    let up = action_state.pressed(Action::Jump);
+    let left = action_state.pressed(Action::MoveLeft);
+    let right = action_state.pressed(Action::MoveRight);
+
+    let direction = if left {
         sprite.flip_x = true;
-        -RUN_POWER
+        -1.0
     } else if right {
         sprite.flip_x = false;
-        RUN_POWER
+        1.0
     } else {
         0.0
     };
+    velocity.linvel.x =
+        tuning.run_speed(velocity.linvel.x, direction, grounded.0, time.delta_secs());
 
-    if up {
-        for tile in tile_info.iter() {
-            let Some(contact_pair) = rapier_context.single().contact_pair(player, tile) else {
-                continue;
-            };
-            for manifold in contact_pair.manifolds() {
-                let first_entity = manifold
-                    .rigid_body1()
-                    .expect("An entity is expected when collision is detected!");
-                if (first_entity == player && manifold.normal().y == -1.0)
-                    || manifold.normal().y == 1.0
-                {
-                    velocity.linvel.y = JUMP_POWER;
-                    break;
-                }
-            }
-        }
+    // The ground probe still finds the floor for a few ticks after a jump, which must not count
+    // as landing, or the coyote time would allow a second jump in the air.
+    if !grounded.0 || velocity.linvel.y <= 0.0 {
+        jump_windows.taking_off = false;
+    }
+    let on_ground = grounded.0 && !jump_windows.taking_off;
+    jump_windows.coyote = if on_ground {
+        tuning.coyote_time
+    } else {
+        jump_windows.coyote - time.delta_secs()
+    };
+    jump_windows.buffer = if up && !jump_windows.jump_held {
+        tuning.jump_buffer_time
+    } else {
+        jump_windows.buffer - time.delta_secs()
+    };
+    jump_windows.jump_held = up;
+
+    // Holding the key still jumps again on every landing, like before the buffer existed.
+    let wants_jump = up || jump_windows.buffer > 0.0;
+    let can_jump = on_ground || jump_windows.coyote > 0.0;
+    if wants_jump && can_jump {
+        velocity.linvel.y = JUMP_POWER;
+        jump_windows.coyote = 0.0;
+        jump_windows.buffer = 0.0;
+        jump_windows.rising = true;
+        jump_windows.taking_off = true;
+    } else if velocity.linvel.y <= 0.0 {
+        jump_windows.rising = false;
+    } else if jump_windows.rising && !up {
+        velocity.linvel.y *= tuning.jump_cut;
+        jump_windows.rising = false;
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::actions::{update_action_state_system, InputMap};
 
     #[test]
     fn test_running_changes_velocity_horizontally() {
@@ -127,15 +267,30 @@ mod tests {
         let mut input = ButtonInput::<KeyCode>::default();
         let player = app
             .world_mut()
-            .spawn((Player, Velocity::default(), Sprite::default()))
+            .spawn((
+                Player,
+                Grounded::default(),
+                JumpWindows::default(),
+                Velocity::default(),
+                Sprite::default(),
+            ))
             .id();
-        app.world_mut().spawn(RapierContext::default());
-        app.insert_resource(ButtonInput::<KeyCode>::default())
-            .add_systems(Update, player_movement_system);
+        app.init_resource::<Time>()
+            .insert_resource(ButtonInput::<KeyCode>::default())
+            .init_resource::<InputMap>()
+            .init_resource::<ActionState>()
+            .init_resource::<MovementTuning>()
+            .add_systems(
+                Update,
+                (update_action_state_system, player_movement_system).chain(),
+            );
 
         // When
         input.press(KeyCode::ArrowRight);
         app.insert_resource(input);
+        app.world_mut()
+            .resource_mut::<Time>()
+            .advance_by(std::time::Duration::from_secs(1));
         app.update();
 
         // Then
@@ -153,15 +308,26 @@ mod tests {
             .world_mut()
             .spawn((
                 Player,
+                Grounded::default(),
+                JumpWindows::default(),
                 Velocity::linear(Vec2::new(RUN_POWER, 0.0)),
                 Sprite::default(),
             ))
             .id();
-        app.world_mut().spawn(RapierContext::default());
-        app.insert_resource(ButtonInput::<KeyCode>::default())
-            .add_systems(Update, player_movement_system);
+        app.init_resource::<Time>()
+            .insert_resource(ButtonInput::<KeyCode>::default())
+            .init_resource::<InputMap>()
+            .init_resource::<ActionState>()
+            .init_resource::<MovementTuning>()
+            .add_systems(
+                Update,
+                (update_action_state_system, player_movement_system).chain(),
+            );
 
         // When
+        app.world_mut()
+            .resource_mut::<Time>()
+            .advance_by(std::time::Duration::from_secs(1));
         app.update();
 
         // Then
@@ -180,6 +346,8 @@ mod tests {
             .world_mut()
             .spawn((
                 Player,
+                Grounded::default(),
+                JumpWindows::default(),
                 Velocity::default(),
                 Sprite {
                     flip_x: false,
@@ -187,9 +355,15 @@ mod tests {
                 },
             ))
             .id();
-        app.world_mut().spawn(RapierContext::default());
-        app.insert_resource(ButtonInput::<KeyCode>::default())
-            .add_systems(Update, player_movement_system);
+        app.init_resource::<Time>()
+            .insert_resource(ButtonInput::<KeyCode>::default())
+            .init_resource::<InputMap>()
+            .init_resource::<ActionState>()
+            .init_resource::<MovementTuning>()
+            .add_systems(
+                Update,
+                (update_action_state_system, player_movement_system).chain(),
+            );
 
         // When
         input.press(KeyCode::ArrowLeft);
@@ -211,6 +385,8 @@ mod tests {
             .world_mut()
             .spawn((
                 Player,
+                Grounded::default(),
+                JumpWindows::default(),
                 Velocity::default(),
                 Sprite {
                     flip_x: true,
@@ -218,9 +394,15 @@ mod tests {
                 },
             ))
             .id();
-        app.world_mut().spawn(RapierContext::default());
-        app.insert_resource(ButtonInput::<KeyCode>::default())
-            .add_systems(Update, player_movement_system);
+        app.init_resource::<Time>()
+            .insert_resource(ButtonInput::<KeyCode>::default())
+            .init_resource::<InputMap>()
+            .init_resource::<ActionState>()
+            .init_resource::<MovementTuning>()
+            .add_systems(
+                Update,
+                (update_action_state_system, player_movement_system).chain(),
+            );
 
         // When
         input.press(KeyCode::ArrowRight);
@@ -241,6 +423,8 @@ mod tests {
             .world_mut()
             .spawn((
                 Player,
+                Grounded::default(),
+                JumpWindows::default(),
                 Velocity::default(),
                 Sprite {
                     flip_x: true,
@@ -248,9 +432,15 @@ mod tests {
                 },
             ))
             .id();
-        app.world_mut().spawn(RapierContext::default());
-        app.insert_resource(ButtonInput::<KeyCode>::default())
-            .add_systems(Update, player_movement_system);
+        app.init_resource::<Time>()
+            .insert_resource(ButtonInput::<KeyCode>::default())
+            .init_resource::<InputMap>()
+            .init_resource::<ActionState>()
+            .init_resource::<MovementTuning>()
+            .add_systems(
+                Update,
+                (update_action_state_system, player_movement_system).chain(),
+            );
 
         // When
         app.update();
@@ -270,6 +460,8 @@ mod tests {
             .world_mut()
             .spawn((
                 Player,
+                Grounded::default(),
+                JumpWindows::default(),
                 Velocity::default(),
                 Sprite::default(),
                 Collider::cuboid(10.0, 10.0),
@@ -286,7 +478,18 @@ mod tests {
         app.init_resource::<Time>()
             .insert_resource(ButtonInput::<KeyCode>::default())
             .add_plugins(RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(100.0))
-            .add_systems(Update, player_movement_system);
+            .init_resource::<InputMap>()
+            .init_resource::<ActionState>()
+            .init_resource::<MovementTuning>()
+            .add_systems(
+                Update,
+                (
+                    update_action_state_system,
+                    grounded_system,
+                    player_movement_system,
+                )
+                    .chain(),
+            );
 
         // When
         app.update();
@@ -310,6 +513,8 @@ mod tests {
             .world_mut()
             .spawn((
                 Player,
+                Grounded::default(),
+                JumpWindows::default(),
                 Velocity::default(),
                 Sprite::default(),
                 Collider::cuboid(10.0, 10.0),
@@ -325,7 +530,18 @@ mod tests {
         app.init_resource::<Time>()
             .insert_resource(ButtonInput::<KeyCode>::default())
             .add_plugins(RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(100.0))
-            .add_systems(Update, player_movement_system);
+            .init_resource::<InputMap>()
+            .init_resource::<ActionState>()
+            .init_resource::<MovementTuning>()
+            .add_systems(
+                Update,
+                (
+                    update_action_state_system,
+                    grounded_system,
+                    player_movement_system,
+                )
+                    .chain(),
+            );
 
         // When
         app.update();
@@ -349,6 +565,8 @@ mod tests {
             .world_mut()
             .spawn((
                 Player,
+                Grounded::default(),
+                JumpWindows::default(),
                 Velocity::default(),
                 Sprite::default(),
                 Collider::cuboid(10.0, 10.0),
@@ -365,7 +583,73 @@ mod tests {
         app.init_resource::<Time>()
             .insert_resource(ButtonInput::<KeyCode>::default())
             .add_plugins(RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(100.0))
-            .add_systems(Update, player_movement_system);
+            .init_resource::<InputMap>()
+            .init_resource::<ActionState>()
+            .init_resource::<MovementTuning>()
+            .add_systems(
+                Update,
+                (
+                    update_action_state_system,
+                    grounded_system,
+                    player_movement_system,
+                )
+                    .chain(),
+            );
+
+        // When
+        app.update();
+        input.press(KeyCode::ArrowUp);
+        app.insert_resource(input);
+        app.update();
+
+        // Then
+        let velocity = app.world().get::<Velocity>(player).unwrap();
+        assert_eq!(velocity.linvel.x, 0.0);
+        assert_eq!(velocity.linvel.y, 0.0);
+    }
+
+    #[test]
+    fn test_jumping_on_the_seam_between_ground_tiles_changes_velocity_vertically() {
+        // Given
+        let mut app = App::new();
+
+        let mut input = ButtonInput::<KeyCode>::default();
+        let player = app
+            .world_mut()
+            .spawn((
+                Player,
+                Grounded::default(),
+                JumpWindows::default(),
+                Velocity::default(),
+                Sprite::default(),
+                Collider::cuboid(10.0, 10.0),
+                Transform::from_xyz(0.0, 0.0, 0.0),
+                RigidBody::Dynamic,
+            ))
+            .id();
+        for x in [-10.0, 10.0] {
+            app.world_mut().spawn((
+                Ground,
+                Collider::cuboid(10.0, 10.0),
+                Transform::from_xyz(x, -20.0, 0.0),
+                RigidBody::Fixed,
+            ));
+        }
+        app.init_resource::<Time>()
+            .insert_resource(ButtonInput::<KeyCode>::default())
+            .add_plugins(RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(100.0))
+            .init_resource::<InputMap>()
+            .init_resource::<ActionState>()
+            .init_resource::<MovementTuning>()
+            .add_systems(
+                Update,
+                (
+                    update_action_state_system,
+                    grounded_system,
+                    player_movement_system,
+                )
+                    .chain(),
+            );
 
         // When
         app.update();
@@ -376,6 +660,150 @@ mod tests {
         // Then
         let velocity = app.world().get::<Velocity>(player).unwrap();
         assert_eq!(velocity.linvel.x, 0.0);
+        assert_eq!(velocity.linvel.y, JUMP_POWER);
+    }
+
+    fn spawn_jumping_app(grounded: bool) -> (App, Entity) {
+        let mut app = App::new();
+
+        let player = app
+            .world_mut()
+            .spawn((
+                Player,
+                Grounded(grounded),
+                JumpWindows::default(),
+                Velocity::default(),
+                Sprite::default(),
+            ))
+            .id();
+        app.init_resource::<Time>()
+            .insert_resource(ButtonInput::<KeyCode>::default())
+            .init_resource::<InputMap>()
+            .init_resource::<ActionState>()
+            .init_resource::<MovementTuning>()
+            .add_systems(
+                Update,
+                (update_action_state_system, player_movement_system).chain(),
+            );
+        app.update();
+        (app, player)
+    }
+
+    fn advance_and_update(app: &mut App, seconds: f32, jump: bool) {
+        let mut input = ButtonInput::<KeyCode>::default();
+        if jump {
+            input.press(KeyCode::ArrowUp);
+        }
+        app.insert_resource(input);
+        app.world_mut()
+            .resource_mut::<Time>()
+            .advance_by(std::time::Duration::from_secs_f32(seconds));
+        app.update();
+    }
+
+    #[test]
+    fn test_jumping_shortly_after_walking_off_a_ledge_changes_velocity_vertically() {
+        // Given
+        let (mut app, player) = spawn_jumping_app(true);
+        let coyote_time = app.world().resource::<MovementTuning>().coyote_time;
+        app.world_mut().get_mut::<Grounded>(player).unwrap().0 = false;
+        advance_and_update(&mut app, coyote_time / 2.0, false);
+
+        // When
+        advance_and_update(&mut app, coyote_time / 4.0, true);
+
+        // Then
+        let velocity = app.world().get::<Velocity>(player).unwrap();
+        assert_eq!(velocity.linvel.y, JUMP_POWER);
+    }
+
+    #[test]
+    fn test_ground_left_behind_by_a_jump_does_not_allow_a_second_jump() {
+        // Given
+        let tick = 1.0 / 240.0;
+        let (mut app, player) = spawn_jumping_app(true);
+        advance_and_update(&mut app, tick, true);
+        // The ground probe lags behind the take-off at high tick rates.
+        advance_and_update(&mut app, tick, false);
+        advance_and_update(&mut app, tick, false);
+        app.world_mut().get_mut::<Grounded>(player).unwrap().0 = false;
+        advance_and_update(&mut app, tick, false);
+        let velocity_after_jump = app.world().get::<Velocity>(player).unwrap().linvel.y;
+
+        // When
+        advance_and_update(&mut app, tick, true);
+
+        // Then
+        let velocity = app.world().get::<Velocity>(player).unwrap();
+        assert!(velocity_after_jump < JUMP_POWER);
+        assert_eq!(velocity.linvel.y, velocity_after_jump);
+    }
+
+    #[test]
+    fn test_jumping_long_after_walking_off_a_ledge_does_not_change_velocity_vertically() {
+        // Given
+        let (mut app, player) = spawn_jumping_app(true);
+        let coyote_time = app.world().resource::<MovementTuning>().coyote_time;
+        app.world_mut().get_mut::<Grounded>(player).unwrap().0 = false;
+        advance_and_update(&mut app, coyote_time * 2.0, false);
+
+        // When
+        advance_and_update(&mut app, 0.01, true);
+
+        // Then
+        let velocity = app.world().get::<Velocity>(player).unwrap();
         assert_eq!(velocity.linvel.y, 0.0);
     }
+
+    #[test]
+    fn test_jump_pressed_just_before_landing_changes_velocity_vertically() {
+        // Given
+        let (mut app, player) = spawn_jumping_app(false);
+        let jump_buffer_time = app.world().resource::<MovementTuning>().jump_buffer_time;
+        advance_and_update(&mut app, 0.01, true);
+        advance_and_update(&mut app, jump_buffer_time / 4.0, false);
+
+        // When
+        app.world_mut().get_mut::<Grounded>(player).unwrap().0 = true;
+        advance_and_update(&mut app, jump_buffer_time / 4.0, false);
+
+        // Then
+        let velocity = app.world().get::<Velocity>(player).unwrap();
+        assert_eq!(velocity.linvel.y, JUMP_POWER);
+    }
+
+    #[test]
+    fn test_running_speeds_up_faster_on_ground_than_in_the_air() {
+        // Given
+        let tuning = MovementTuning::default();
+
+        // When
+        let on_ground = tuning.run_speed(0.0, 1.0, true, 0.05);
+        let in_the_air = tuning.run_speed(0.0, 1.0, false, 0.05);
+
+        // Then
+        assert_eq!(on_ground, tuning.ground_acceleration * 0.05);
+        assert_eq!(in_the_air, tuning.air_acceleration * 0.05);
+        assert!(on_ground > in_the_air);
+        assert_eq!(
+            tuning.run_speed(RUN_POWER - 1.0, 1.0, true, 0.05),
+            RUN_POWER
+        );
+    }
+
+    #[test]
+    fn test_releasing_jump_early_cuts_the_jump_short() {
+        // Given
+        let (mut app, player) = spawn_jumping_app(true);
+        advance_and_update(&mut app, 0.01, true);
+        app.world_mut().get_mut::<Grounded>(player).unwrap().0 = false;
+
+        // When
+        advance_and_update(&mut app, 0.01, false);
+
+        // Then
+        let velocity = app.world().get::<Velocity>(player).unwrap();
+        let jump_cut = app.world().resource::<MovementTuning>().jump_cut;
+        assert_eq!(velocity.linvel.y, JUMP_POWER * jump_cut);
+    }
 }
@@ -1,4 +1,4 @@
-use super::Player;
+use super::{Grounded, Player};
 use crate::GameState;
 use bevy::{prelude::*, utils::HashMap};
 use bevy_rapier2d::prelude::*;
@@ -9,13 +9,17 @@ impl Plugin for AnimationsPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
             Update,
+            animate_player_system.run_if(in_state(GameState::MapExploring)),
+        )
+        .add_systems(
+            FixedUpdate,
             (
-                animate_player_system,
                 idle_animation_trigger_system,
                 run_animation_trigger_system,
                 jump_animation_trigger_system,
                 fall_animation_trigger_system,
             )
+                .after(super::grounded_system)
                 .run_if(in_state(GameState::MapExploring)),
         );
     }
@@ -73,47 +77,29 @@ impl AnimationInfo {
 }
 
 fn idle_animation_trigger_system(
-    rapier_context: Query<&RapierContext>,
-    mut animation_info: Query<(Entity, &mut AnimationInfo, &Velocity), With<Player>>,
+    mut animation_info: Query<(&mut AnimationInfo, &Velocity, &Grounded), With<Player>>,
 ) {
-    let (player, mut animation_info, velocity) = animation_info.single_mut();
+    let (mut animation_info, velocity, grounded) = animation_info.single_mut();
     if [AnimationType::Idle, AnimationType::Jump].contains(&animation_info.current_animation_type) {
         return;
     }
-    if velocity.linvel.x != 0.0 {
+    if velocity.linvel.x != 0.0 || !grounded.0 {
         return;
     }
-    for contact_pair in rapier_context.single().contact_pairs_with(player) {
-        for manifold in contact_pair.manifolds() {
-            if manifold.normal().y == 0.0 {
-                continue;
-            }
-            animation_info.set_animation(AnimationType::Idle);
-            return;
-        }
-    }
+    animation_info.set_animation(AnimationType::Idle);
 }
 
 fn run_animation_trigger_system(
-    rapier_context: Query<&RapierContext>,
-    mut animation_info: Query<(Entity, &mut AnimationInfo, &Velocity), With<Player>>,
+    mut animation_info: Query<(&mut AnimationInfo, &Velocity, &Grounded), With<Player>>,
 ) {
-    let (player, mut animation_info, velocity) = animation_info.single_mut();
+    let (mut animation_info, velocity, grounded) = animation_info.single_mut();
     if animation_info.current_animation_type == AnimationType::Jump {
         return;
     }
-    if velocity.linvel.x == 0.0 {
+    if velocity.linvel.x == 0.0 || !grounded.0 {
         return;
     }
-    for contact_pair in rapier_context.single().contact_pairs_with(player) {
-        for manifold in contact_pair.manifolds() {
-            if manifold.normal().y == 0.0 {
-                continue;
-            }
-            animation_info.set_animation(AnimationType::Run);
-            return;
-        }
-    }
+    animation_info.set_animation(AnimationType::Run);
 }
 
 fn jump_animation_trigger_system(
@@ -142,23 +128,15 @@ fn jump_animation_trigger_system(
 }
 
 fn fall_animation_trigger_system(
-    rapier_context: Query<&RapierContext>,
-    mut animation_info: Query<(Entity, &mut AnimationInfo, &Velocity), With<Player>>,
+    mut animation_info: Query<(&mut AnimationInfo, &Velocity, &Grounded), With<Player>>,
 ) {
-    let (player, mut animation_info, velocity) = animation_info.single_mut();
+    let (mut animation_info, velocity, grounded) = animation_info.single_mut();
     if animation_info.current_animation_type == AnimationType::Fall {
         return;
     }
-    if velocity.linvel.y > 0.0 {
+    if velocity.linvel.y > 0.0 || grounded.0 {
         return;
     }
-    for contact_pair in rapier_context.single().contact_pairs_with(player) {
-        for manifold in contact_pair.manifolds() {
-            if manifold.normal().y != 0.0 {
-                return;
-            }
-        }
-    }
     animation_info.set_animation(AnimationType::Fall);
 }
 
@@ -0,0 +1,402 @@
+use crate::{
+    actions::{update_action_state_system, InputMap},
+    physics::TickRate,
+    save::{current_save_data, load_detached, CurrentSlot, SaveData, SaveSlots},
+    GameState,
+};
+use bevy::{
+    input::{keyboard::KeyboardInput, InputSystem},
+    prelude::*,
+    time::TimeUpdateStrategy,
+};
+use bevy_ecs_ldtk::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{fs, mem, path::PathBuf};
+
+/// Records the keyboard to a file, or plays a recording back instead of the keyboard.
+///
+/// A recording runs in real time and keeps the input of every physics tick, along with the
+/// controls and the tick rate it was played with. A replay uses those again and runs exactly one
+/// tick per frame, so it moves the player exactly like the recorded session did. Recording starts
+/// when the first level starts loading, together with the progress it was loaded from. Ticks
+/// spent loading a level are skipped, since loading takes a different number of them each time.
+/// Mouse and gamepad input are not recorded.
+pub enum ReplayPlugin {
+    Record(PathBuf),
+    Replay(PathBuf),
+}
+
+impl ReplayPlugin {
+    /// Reads `--record <file>` or `--replay <file>` from the command line arguments.
+    pub fn from_args(mut args: impl Iterator<Item = String>) -> Option<Self> {
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--record" => return args.next().map(|path| Self::Record(path.into())),
+                "--replay" => return args.next().map(|path| Self::Replay(path.into())),
+                _ => {}
+            }
+        }
+        None
+    }
+}
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        match self {
+            ReplayPlugin::Record(path) => {
+                app.insert_resource(Recorder {
+                    path: path.clone(),
+                    recording: None,
+                    pending_events: Vec::new(),
+                })
+                .add_systems(OnEnter(GameState::LevelLoading), start_recording_system)
+                .add_systems(PreUpdate, collect_events_system.after(InputSystem))
+                .add_systems(FixedFirst, record_tick_system)
+                .add_systems(Last, write_recording_system);
+            }
+            ReplayPlugin::Replay(path) => {
+                let recording: Recording = fs::read_to_string(path)
+                    .map_err(|error| error.to_string())
+                    .and_then(|content| ron::from_str(&content).map_err(|error| error.to_string()))
+                    .unwrap_or_else(|error| {
+                        panic!("Could not read the recording {path:?}: {error}!")
+                    });
+                let tick_rate = TickRate(recording.tick_rate);
+                app.insert_resource(TimeUpdateStrategy::ManualDuration(tick_rate.tick()))
+                    .insert_resource(tick_rate)
+                    .insert_resource(recording.input_map.clone())
+                    .insert_resource(Replayer {
+                        recording,
+                        next_tick: None,
+                    })
+                    .add_systems(Startup, start_replay_system)
+                    .add_systems(OnEnter(GameState::LevelLoading), start_ticks_system)
+                    .add_systems(PreUpdate, drop_keyboard_events_system.before(InputSystem))
+                    .add_systems(
+                        FixedFirst,
+                        (replay_tick_system, update_action_state_system).chain(),
+                    )
+                    .add_systems(PostUpdate, keep_recorded_controls_system);
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Recording {
+    level: String,
+    save: Option<SaveData>,
+    input_map: InputMap,
+    tick_rate: f64,
+    ticks: Vec<Tick>,
+}
+
+/// The keys held during a tick, and the events that led there since the tick before.
+#[derive(Default, Serialize, Deserialize)]
+struct Tick {
+    pressed: Vec<KeyCode>,
+    events: Vec<KeyboardInput>,
+}
+
+#[derive(Resource)]
+struct Recorder {
+    path: PathBuf,
+    recording: Option<Recording>,
+    pending_events: Vec<KeyboardInput>,
+}
+
+#[derive(Resource)]
+struct Replayer {
+    recording: Recording,
+    next_tick: Option<usize>,
+}
+
+fn start_recording_system(
+    save_slots: Res<SaveSlots>,
+    current_slot: Res<CurrentSlot>,
+    current_level: Res<LevelSelection>,
+    input_map: Res<InputMap>,
+    tick_rate: Res<TickRate>,
+    mut recorder: ResMut<Recorder>,
+) {
+    if recorder.recording.is_some() {
+        return;
+    }
+    let LevelSelection::Iid(level) = &*current_level else {
+        return;
+    };
+    recorder.recording = Some(Recording {
+        level: level.to_string(),
+        save: current_save_data(&save_slots, &current_slot),
+        input_map: input_map.clone(),
+        tick_rate: tick_rate.clamped(),
+        ticks: Vec::new(),
+    });
+}
+
+/// Keeps the keyboard events of this frame until the next tick records them.
+fn collect_events_system(
+    mut recorder: ResMut<Recorder>,
+    mut keyboard_events: EventReader<KeyboardInput>,
+) {
+    if recorder.recording.is_none() {
+        keyboard_events.clear();
+        return;
+    }
+    recorder
+        .pending_events
+        .extend(keyboard_events.read().cloned());
+}
+
+fn record_tick_system(
+    mut recorder: ResMut<Recorder>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    state: Res<State<GameState>>,
+) {
+    let events = mem::take(&mut recorder.pending_events);
+    if *state == GameState::LevelLoading {
+        return;
+    }
+    let Some(recording) = &mut recorder.recording else {
+        return;
+    };
+    recording.ticks.push(Tick {
+        pressed: Vec::from_iter(keyboard_input.get_pressed().copied()),
+        events,
+    });
+}
+
+fn write_recording_system(recorder: Res<Recorder>, mut exit: EventReader<AppExit>) {
+    if exit.read().next().is_none() {
+        return;
+    }
+    let Some(recording) = &recorder.recording else {
+        return;
+    };
+    let content = ron::ser::to_string_pretty(
+        recording,
+        ron::ser::PrettyConfig::default().compact_arrays(true),
+    )
+    .expect("A recording is always serializable!");
+    match fs::write(&recorder.path, content) {
+        Ok(()) => info!(
+            "Recorded {} ticks to {:?}",
+            recording.ticks.len(),
+            recorder.path
+        ),
+        Err(error) => warn!("Could not write the recording {:?}: {error}", recorder.path),
+    }
+}
+
+fn start_replay_system(
+    mut commands: Commands,
+    replayer: Res<Replayer>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    match &replayer.recording.save {
+        Some(save) => load_detached(&mut commands, save.clone()),
+        None => commands.insert_resource(LevelSelection::iid(replayer.recording.level.clone())),
+    }
+    next_state.set(GameState::LevelLoading);
+}
+
+fn start_ticks_system(mut replayer: ResMut<Replayer>) {
+    if replayer.next_tick.is_none() {
+        replayer.next_tick = Some(0);
+    }
+}
+
+/// Ignores the keyboard once the replay runs, along with the events replayed last frame.
+fn drop_keyboard_events_system(
+    replayer: Res<Replayer>,
+    mut keyboard_events: ResMut<Events<KeyboardInput>>,
+) {
+    if replayer.next_tick.is_some() {
+        keyboard_events.clear();
+    }
+}
+
+/// Sends the recorded events of the tick and holds exactly the recorded keys.
+fn replay_tick_system(
+    mut replayer: ResMut<Replayer>,
+    mut keyboard_events: EventWriter<KeyboardInput>,
+    mut keyboard_input: ResMut<ButtonInput<KeyCode>>,
+    state: Res<State<GameState>>,
+) {
+    let Some(next_tick) = replayer.next_tick else {
+        return;
+    };
+    if *state == GameState::LevelLoading {
+        return;
+    }
+    let Some(tick) = replayer.recording.ticks.get(next_tick) else {
+        if next_tick == replayer.recording.ticks.len() {
+            info!("Replayed all {next_tick} ticks");
+            replayer.next_tick = Some(next_tick + 1);
+        }
+        return;
+    };
+    keyboard_events.send_batch(tick.events.iter().cloned());
+    let released = Vec::from_iter(
+        keyboard_input
+            .get_pressed()
+            .filter(|key| !tick.pressed.contains(key))
+            .copied(),
+    );
+    for key in released {
+        keyboard_input.release(key);
+    }
+    for key in tick.pressed.iter() {
+        keyboard_input.press(*key);
+    }
+    replayer.next_tick = Some(next_tick + 1);
+}
+
+/// Puts back the recorded controls and tick rate, should the settings overwrite them.
+fn keep_recorded_controls_system(
+    replayer: Res<Replayer>,
+    mut input_map: ResMut<InputMap>,
+    mut tick_rate: ResMut<TickRate>,
+) {
+    input_map.set_if_neq(replayer.recording.input_map.clone());
+    tick_rate.set_if_neq(TickRate(replayer.recording.tick_rate));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::{
+        input::{keyboard::Key, ButtonState, InputPlugin},
+        state::app::StatesPlugin,
+    };
+    use std::time::Duration;
+
+    fn key_event(key_code: KeyCode, state: ButtonState) -> KeyboardInput {
+        KeyboardInput {
+            key_code,
+            logical_key: Key::ArrowRight,
+            state,
+            repeat: false,
+            window: Entity::PLACEHOLDER,
+        }
+    }
+
+    fn recording(ticks: Vec<Tick>) -> Recording {
+        Recording {
+            level: "level".to_string(),
+            save: None,
+            input_map: InputMap::default(),
+            tick_rate: 60.0,
+            ticks,
+        }
+    }
+
+    fn input_app(frame: Duration, tick_rate: TickRate) -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, InputPlugin, StatesPlugin))
+            .insert_state(GameState::MapExploring)
+            .insert_resource(TimeUpdateStrategy::ManualDuration(frame))
+            .insert_resource(Time::<Fixed>::from_duration(tick_rate.tick()));
+        app
+    }
+
+    #[test]
+    fn test_replay_feeds_the_recorded_ticks_instead_of_the_keyboard() {
+        // Given
+        let recording = recording(vec![
+            Tick {
+                pressed: vec![KeyCode::ArrowRight],
+                events: vec![key_event(KeyCode::ArrowRight, ButtonState::Pressed)],
+            },
+            Tick {
+                pressed: vec![KeyCode::ArrowRight],
+                events: vec![],
+            },
+            Tick {
+                pressed: vec![],
+                events: vec![key_event(KeyCode::ArrowRight, ButtonState::Released)],
+            },
+        ]);
+        let recording: Recording = ron::from_str(&ron::to_string(&recording).unwrap()).unwrap();
+        let tick_rate = TickRate(recording.tick_rate);
+        let mut app = input_app(tick_rate.tick(), tick_rate);
+        app.insert_resource(Replayer {
+            recording,
+            next_tick: Some(0),
+        })
+        .add_systems(PreUpdate, drop_keyboard_events_system.before(InputSystem))
+        .add_systems(FixedFirst, replay_tick_system);
+        // The first frame does not advance the time.
+        app.update();
+
+        // When
+        let mut pressed = Vec::new();
+        for _ in 0..4 {
+            app.world_mut()
+                .send_event(key_event(KeyCode::KeyA, ButtonState::Pressed));
+            app.update();
+            let keyboard_input = app.world().resource::<ButtonInput<KeyCode>>();
+            pressed.push(Vec::from_iter(keyboard_input.get_pressed().copied()));
+        }
+
+        // Then
+        assert_eq!(
+            pressed,
+            vec![
+                vec![KeyCode::ArrowRight],
+                vec![KeyCode::ArrowRight],
+                vec![],
+                vec![],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_recording_keeps_one_tick_per_physics_tick() {
+        // Given
+        let mut app = input_app(Duration::from_secs(1) / 150, TickRate(60.0));
+        app.insert_resource(Recorder {
+            path: PathBuf::new(),
+            recording: Some(recording(Vec::new())),
+            pending_events: Vec::new(),
+        })
+        .add_systems(PreUpdate, collect_events_system.after(InputSystem))
+        .add_systems(FixedFirst, record_tick_system);
+
+        // When
+        app.world_mut()
+            .send_event(key_event(KeyCode::ArrowRight, ButtonState::Pressed));
+        for _ in 0..30 {
+            app.update();
+        }
+
+        // Then
+        let ticks = &app
+            .world()
+            .resource::<Recorder>()
+            .recording
+            .as_ref()
+            .unwrap()
+            .ticks;
+        let elapsed = app.world().resource::<Time<Fixed>>().elapsed_secs_f64();
+        assert_eq!(ticks.len(), (elapsed * 60.0).round() as usize);
+        assert!(ticks.len() < 30);
+        assert_eq!(ticks[0].pressed, vec![KeyCode::ArrowRight]);
+        assert_eq!(ticks[0].events.len(), 1);
+        assert!(ticks[1..].iter().all(|tick| tick.events.is_empty()));
+    }
+
+    #[test]
+    fn test_from_args_reads_the_mode_and_the_file() {
+        // Given
+        let args = ["puzzle-up", "--replay", "bug.ron"].map(String::from);
+
+        // When
+        let plugin = ReplayPlugin::from_args(args.into_iter());
+
+        // Then
+        assert!(
+            matches!(plugin, Some(ReplayPlugin::Replay(path)) if path.to_str() == Some("bug.ron"))
+        );
+    }
+}
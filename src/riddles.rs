@@ -1,5 +1,8 @@
+use crate::actions::{consume_pressed_actions_system, Action, ActionState};
+use crate::map::{EntityError, TryFromEntity};
 use crate::player::Player;
 use crate::GameState;
+use answer::{AnswerKind, AnswerNormalization};
 use bevy::{
     input::keyboard::{Key, KeyboardInput},
     prelude::*,
@@ -8,42 +11,154 @@ use bevy::{
 use bevy_ecs_ldtk::prelude::*;
 use bevy_rapier2d::prelude::*;
 
+pub mod answer;
 mod nodes;
 pub struct RiddlesPlugin;
 
 impl Plugin for RiddlesPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(AnsweredRiddles::default())
+            .add_event::<WrongAnswer>()
+            .add_event::<RiddleInput>()
+            .add_systems(OnEnter(GameState::LevelLoading), clear_riddles_system)
+            .add_systems(OnEnter(GameState::MainMenu), clear_riddles_system)
             .add_systems(OnExit(GameState::LevelLoading), init_riddles_system)
+            .add_systems(
+                OnEnter(GameState::RiddleSolving),
+                consume_pressed_actions_system,
+            )
+            .add_systems(
+                OnExit(GameState::RiddleSolving),
+                consume_pressed_actions_system,
+            )
             .add_systems(
                 Update,
                 touch_door_system.run_if(in_state(GameState::MapExploring)),
             )
+            .add_systems(
+                Update,
+                keyboard_riddle_input_system.before(answering_riddle_system),
+            )
             .add_systems(
                 Update,
                 (
-                    answering_riddle_system,
-                    delete_digit_system,
-                    (correct_answer_system, clear_input_system).chain(),
-                    close_riddle_system,
+                    keypad_system,
+                    (
+                        (
+                            answering_riddle_system,
+                            delete_digit_system,
+                            move_caret_system,
+                        )
+                            .chain(),
+                        pick_character_system,
+                        caret_system
+                            .after(move_caret_system)
+                            .after(clear_input_system),
+                        // A correct answer despawns the riddle, so it goes after every other input.
+                        (correct_answer_system, clear_input_system)
+                            .chain()
+                            .after(move_caret_system)
+                            .after(pick_character_system),
+                        close_riddle_system,
+                        (wrong_answer_reaction_system, wrong_answer_feedback_system).chain(),
+                        reveal_hint_system.after(correct_answer_system),
+                    ),
                 )
+                    .chain()
                     .run_if(in_state(GameState::RiddleSolving)),
             );
     }
 }
 
+/// The doors whose riddles have been solved, keyed by their LDtk entity IID.
 #[derive(Default, Resource)]
-struct AnsweredRiddles {
+pub struct AnsweredRiddles {
     ids: HashSet<String>,
 }
 
+impl FromIterator<String> for AnsweredRiddles {
+    fn from_iter<T: IntoIterator<Item = String>>(ids: T) -> Self {
+        Self {
+            ids: HashSet::from_iter(ids),
+        }
+    }
+}
+
+impl AnsweredRiddles {
+    pub fn ids(&self) -> impl Iterator<Item = &str> {
+        self.ids.iter().map(String::as_str)
+    }
+
+    fn contains(&self, door: &EntityIid) -> bool {
+        self.ids.contains(door.as_str())
+    }
+
+    fn insert(&mut self, door: &EntityIid) {
+        self.ids.insert(door.as_str().to_string());
+    }
+
+    /// Progress from before doors were keyed by IID holds question texts instead.
+    /// Every loaded door asking such a question takes the solved state over under
+    /// its own IID, so the legacy IDs disappear level by level as they are visited.
+    fn migrate_legacy_ids<'a>(&mut self, doors: impl Iterator<Item = (&'a EntityIid, &'a str)>) {
+        let migrated = Vec::from_iter(doors.filter(|(_, question)| self.ids.contains(*question)));
+        for (_, question) in migrated.iter() {
+            self.ids.remove(*question);
+        }
+        for (door, _) in migrated {
+            self.insert(door);
+        }
+    }
+}
+
+/// Sent every time a complete but incorrect answer is submitted for a door.
+#[derive(Event)]
+pub struct WrongAnswer {
+    pub door: Entity,
+    pub attempts: u32,
+}
+
+/// What the player does to the open riddle, whether typed or clicked on the keypad.
+#[derive(Clone, Copy, Debug, PartialEq, Event)]
+enum RiddleInput {
+    Character(char),
+    Delete,
+    DeleteNext,
+    PreviousSlot,
+    NextSlot,
+    FirstSlot,
+    LastSlot,
+    Focus(usize),
+    Submit,
+    Close,
+}
+
 #[derive(Component)]
 struct RiddleNode;
 
+#[derive(Component)]
+struct KeypadKey(RiddleInput);
+
+#[derive(Component)]
+struct WrongAnswerMessage;
+
+#[derive(Component)]
+struct Hint {
+    riddle: Entity,
+    index: usize,
+}
+
+#[derive(Component)]
+struct WrongAnswerFeedback {
+    timer: Timer,
+}
+
 #[derive(Component)]
 struct AnswerContainer {
     index: usize,
     answer_length: usize,
+    shortest_answer_length: usize,
+    answer_kind: AnswerKind,
 }
 
 #[derive(Component)]
@@ -51,53 +166,276 @@ struct Answer {
     position: usize,
 }
 
+const MAX_ANSWER_LENGTH: usize = 12;
+const WRONG_ANSWER_FEEDBACK_SECONDS: f32 = 0.5;
+const WRONG_ANSWER_SHAKE_AMPLITUDE: f32 = 20.0;
+const WRONG_ANSWER_SHAKE_FREQUENCY: f32 = 40.0;
+const DEFAULT_SLOT_PALETTE: [Color; 3] = [
+    Color::srgb(1.0, 0.0, 0.0),
+    Color::srgb(0.0, 0.0, 1.0),
+    Color::srgb(1.0, 1.0, 0.0),
+];
+
 #[derive(Default, Component)]
 pub struct RiddleInfo {
     question: String,
-    answer: String,
+    answers: Vec<String>,
+    answers_hashed: bool,
+    answer_length: Option<usize>,
+    shortest_answer_length: Option<usize>,
+    answer_kind: AnswerKind,
+    normalization: AnswerNormalization,
+    slot_colors: Vec<Color>,
     riddle: Option<Entity>,
     next_level: String,
+    attempts: u32,
+    hints: Vec<String>,
+    hint_timer: Option<Timer>,
+    hint_attempts: Option<u32>,
+    revealed_hints: usize,
+}
+
+impl RiddleInfo {
+    fn slot_color(&self, position: usize) -> Color {
+        if self.slot_colors.is_empty() {
+            DEFAULT_SLOT_PALETTE[position % DEFAULT_SLOT_PALETTE.len()]
+        } else {
+            self.slot_colors[position % self.slot_colors.len()]
+        }
+    }
+
+    /// Hashed answers hide their lengths, so those riddles state the shortest and longest instead.
+    fn answer_lengths(&self) -> Vec<usize> {
+        match (self.shortest_answer_length, self.answer_length) {
+            (Some(shortest_answer_length), Some(answer_length)) if self.answers_hashed => {
+                vec![shortest_answer_length, answer_length]
+            }
+            _ => Vec::from_iter(self.answers.iter().map(|answer| answer.chars().count())),
+        }
+    }
+
+    /// Whether submitting `input` in the answer slots opens the door.
+    pub fn solves(&self, input: &str) -> bool {
+        let answer_lengths = self.answer_lengths();
+        let length = input.chars().count();
+        answer_lengths
+            .iter()
+            .min()
+            .is_some_and(|&shortest| length >= shortest)
+            && answer_lengths
+                .iter()
+                .max()
+                .is_some_and(|&longest| length <= longest)
+            && self.is_correct(input)
+    }
+
+    fn is_correct(&self, input: &str) -> bool {
+        if self.answers_hashed {
+            let input = self.normalization.normalize(input);
+            return self
+                .answers
+                .iter()
+                .any(|answer| answer::matches_hash(&input, answer));
+        }
+        self.answers
+            .iter()
+            .any(|answer| self.normalization.matches(input, answer))
+    }
+
+    pub fn question(&self) -> &str {
+        &self.question
+    }
+
+    pub fn next_level(&self) -> &str {
+        &self.next_level
+    }
+
+    fn hint_earned_by_attempts(&self) -> bool {
+        self.hint_attempts.is_some_and(|hint_attempts| {
+            self.attempts >= hint_attempts * (self.revealed_hints as u32 + 1)
+        })
+    }
 }
 
 impl From<&EntityInstance> for RiddleInfo {
     fn from(entity_instance: &EntityInstance) -> Self {
+        // `invalid_entity_system` reports the error and puts a placeholder in its place.
+        Self::try_from_entity(entity_instance).unwrap_or_default()
+    }
+}
+
+impl TryFromEntity for RiddleInfo {
+    fn try_from_entity(entity_instance: &EntityInstance) -> Result<Self, EntityError> {
         let fields = HashMap::from_iter(entity_instance.field_instances.iter().map(|field| {
             (
                 field.identifier.clone(),
                 match &field.value {
-                    FieldValue::String(Some(value)) => value.clone(),
-                    _ => "".to_string(),
+                    FieldValue::String(Some(value)) | FieldValue::Enum(Some(value)) => {
+                        Some(value.clone())
+                    }
+                    _ => None,
                 },
             )
         }));
-        Self {
-            question: fields
-                .get("question")
-                .expect("A question is required for a riddle!")
-                .into(),
-            answer: fields
-                .get("answer")
-                .expect("An answer is required for a riddle!")
-                .into(),
-            next_level: fields
-                .get("next_level")
-                .expect("A next level is required for a riddle!")
-                .into(),
-            ..default()
+        let required = |identifier: &'static str| {
+            fields
+                .get(identifier)
+                .cloned()
+                .flatten()
+                .ok_or(EntityError::MissingField(identifier))
+        };
+        let list_field = |identifier: &str| {
+            entity_instance
+                .field_instances
+                .iter()
+                .find(|field| field.identifier == identifier)
+                .map(|field| match &field.value {
+                    FieldValue::Strings(values) | FieldValue::Enums(values) => {
+                        values.iter().flatten().cloned().collect()
+                    }
+                    _ => Vec::new(),
+                })
+                .unwrap_or_default()
+        };
+        let answer_kind = match fields.get("answer_kind").cloned().flatten() {
+            Some(kind) => AnswerKind::try_from(kind.as_str())
+                .map_err(|reason| EntityError::invalid("answer_kind", reason))?,
+            None => AnswerKind::default(),
+        };
+        let answers = Vec::from_iter(
+            [required("answer")?]
+                .into_iter()
+                .chain(list_field("answers")),
+        );
+        let answers_hashed = entity_instance
+            .field_instances
+            .iter()
+            .find(|field| field.identifier == "answer_hashed")
+            .is_some_and(|field| field.value == FieldValue::Bool(true));
+        let int_field = |identifier: &str| {
+            entity_instance
+                .field_instances
+                .iter()
+                .find(|field| field.identifier == identifier)
+                .and_then(|field| match field.value {
+                    FieldValue::Int(Some(length)) => Some(length as usize),
+                    _ => None,
+                })
+        };
+        let answer_length = int_field("answer_length");
+        let shortest_answer_length = int_field("shortest_answer_length");
+        if answers_hashed {
+            if !answer_length.is_some_and(|length| (1..=MAX_ANSWER_LENGTH).contains(&length)) {
+                return Err(EntityError::invalid(
+                    "answer_length",
+                    format!(
+                        "hashed answers need an answer length between 1 and {MAX_ANSWER_LENGTH}"
+                    ),
+                ));
+            }
+            if !shortest_answer_length
+                .zip(answer_length)
+                .is_some_and(|(shortest, longest)| (1..=longest).contains(&shortest))
+            {
+                return Err(EntityError::invalid(
+                    "shortest_answer_length",
+                    "hashed answers need a shortest answer length between 1 and the answer length",
+                ));
+            }
+            if !answers.iter().all(|answer| answer.contains('$')) {
+                return Err(EntityError::invalid(
+                    "answer",
+                    "a hashed answer is expected to look like salt$hash",
+                ));
+            }
+        }
+        for answer in answers.iter().filter(|_| !answers_hashed) {
+            if !(1..=MAX_ANSWER_LENGTH).contains(&answer.chars().count()) {
+                return Err(EntityError::invalid(
+                    "answer",
+                    format!("{answer:?} is not between 1 and {MAX_ANSWER_LENGTH} characters long"),
+                ));
+            }
+            if !answer
+                .chars()
+                .all(|character| answer_kind.accepts(character))
+            {
+                return Err(EntityError::invalid(
+                    "answer",
+                    format!("{answer:?} does not match the answer kind {answer_kind:?}"),
+                ));
+            }
+        }
+        let normalization = list_field("normalization").iter().try_fold(
+            AnswerNormalization::for_kind(answer_kind),
+            |normalization, step| {
+                normalization
+                    .try_with_step(step)
+                    .map_err(|reason| EntityError::invalid("normalization", reason))
+            },
+        )?;
+        let hint_delay = entity_instance
+            .field_instances
+            .iter()
+            .find(|field| field.identifier == "hint_delay")
+            .and_then(|field| match field.value {
+                FieldValue::Float(Some(delay)) => Some(delay),
+                _ => None,
+            });
+        if let Some(delay) = hint_delay.filter(|delay| !(delay.is_finite() && *delay >= 0.0)) {
+            return Err(EntityError::invalid(
+                "hint_delay",
+                format!("{delay} is not a number of seconds"),
+            ));
         }
+        Ok(Self {
+            question: required("question")?,
+            answers,
+            answers_hashed,
+            answer_length,
+            shortest_answer_length,
+            answer_kind,
+            normalization,
+            slot_colors: entity_instance
+                .field_instances
+                .iter()
+                .find(|field| field.identifier == "slot_colors")
+                .map(|field| match &field.value {
+                    FieldValue::Colors(colors) => colors.clone(),
+                    _ => Vec::new(),
+                })
+                .unwrap_or_default(),
+            next_level: required("next_level")?,
+            hints: list_field("hints"),
+            hint_timer: hint_delay.map(|delay| Timer::from_seconds(delay, TimerMode::Repeating)),
+            hint_attempts: entity_instance
+                .field_instances
+                .iter()
+                .find(|field| field.identifier == "hint_attempts")
+                .and_then(|field| match field.value {
+                    FieldValue::Int(Some(attempts)) if attempts > 0 => Some(attempts as u32),
+                    _ => None,
+                }),
+            ..default()
+        })
     }
 }
 
 fn init_riddles_system(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
-    answered_riddles: Res<AnsweredRiddles>,
-    mut doors: Query<(&mut RiddleInfo, &mut Sprite)>,
+    mut answered_riddles: ResMut<AnsweredRiddles>,
+    mut doors: Query<(&EntityIid, &mut RiddleInfo, &mut Sprite)>,
 ) {
     use nodes::*;
 
-    for (mut door, mut sprite) in doors.iter_mut() {
-        if answered_riddles.ids.contains(&door.question) {
+    answered_riddles.migrate_legacy_ids(
+        doors
+            .iter()
+            .map(|(iid, door, _)| (iid, door.question.as_str())),
+    );
+    for (iid, mut door, mut sprite) in doors.iter_mut() {
+        if answered_riddles.contains(iid) {
             if let Some(sprite) = &mut sprite.texture_atlas {
                 sprite.index = 75;
             }
@@ -109,53 +447,94 @@ fn init_riddles_system(
                 .insert(RiddleNode)
                 .with_children(|parent| {
                     parent.spawn(question_text(&asset_server, &door.question.clone()));
+                    let answer_lengths = door.answer_lengths();
+                    let answer_length = answer_lengths.iter().copied().max().unwrap_or_default();
                     parent
                         .spawn(answer_container())
                         .insert(AnswerContainer {
                             index: 0,
-                            answer_length: door.answer.len(),
+                            answer_length,
+                            shortest_answer_length: answer_lengths
+                                .iter()
+                                .copied()
+                                .min()
+                                .unwrap_or_default(),
+                            answer_kind: door.answer_kind,
                         })
                         .with_children(|parent| {
+                            for position in 0..answer_length {
+                                parent
+                                    .spawn(answer_position(&asset_server))
+                                    .insert(TextColor(door.slot_color(position)))
+                                    .insert(Answer { position });
+                            }
+                        });
+                    parent.spawn(keypad()).with_children(|parent| {
+                        let keys = door
+                            .answer_kind
+                            .picker_characters()
+                            .into_iter()
+                            .map(|character| {
+                                (character.to_string(), RiddleInput::Character(character))
+                            })
+                            .chain([
+                                ("Del".to_string(), RiddleInput::Delete),
+                                ("Enter".to_string(), RiddleInput::Submit),
+                                ("Close".to_string(), RiddleInput::Close),
+                            ]);
+                        for (label, input) in keys {
                             parent
-                                .spawn(answer_position(&asset_server))
-                                .insert(TextColor(Color::srgb(1.0, 0.0, 0.0)))
-                                .insert(Answer { position: 0 });
-                            parent
-                                .spawn(answer_position(&asset_server))
-                                .insert(TextColor(Color::srgb(0.0, 0.0, 1.0)))
-                                .insert(Answer { position: 1 });
+                                .spawn(keypad_key())
+                                .insert(KeypadKey(input))
+                                .with_children(|parent| {
+                                    parent.spawn(keypad_key_text(&asset_server, &label));
+                                });
+                        }
+                    });
+                    parent
+                        .spawn(wrong_answer_text(&asset_server))
+                        .insert(WrongAnswerMessage);
+                    let riddle = parent.parent_entity();
+                    parent.spawn(hint_container()).with_children(|parent| {
+                        for (index, hint) in door.hints.iter().enumerate() {
                             parent
-                                .spawn(answer_position(&asset_server))
-                                .insert(TextColor(Color::srgb(1.0, 1.0, 0.0)))
-                                .insert(Answer { position: 2 });
-                        });
+                                .spawn(hint_text(&asset_server, hint))
+                                .insert(Hint { riddle, index });
+                        }
+                    });
                 })
                 .id(),
         );
     }
 }
 
+fn clear_riddles_system(mut commands: Commands, riddle_nodes: Query<Entity, With<RiddleNode>>) {
+    for riddle in riddle_nodes.iter() {
+        commands.entity(riddle).despawn_recursive();
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 fn touch_door_system(
     answered_riddles: Res<AnsweredRiddles>,
     mut next_state: ResMut<NextState<GameState>>,
-    mut keyboard_input: ResMut<ButtonInput<KeyCode>>,
+    mut action_state: ResMut<ActionState>,
     mut current_level: ResMut<LevelSelection>,
     rapier_context: Query<&RapierContext>,
     player_info: Query<Entity, With<Player>>,
-    mut doors: Query<(Entity, &mut RiddleInfo)>,
+    mut doors: Query<(Entity, &EntityIid, &mut RiddleInfo)>,
     mut riddle_nodes: Query<&mut Visibility, With<RiddleNode>>,
 ) {
     let player = player_info.single();
-    if !keyboard_input.just_pressed(KeyCode::Space) {
+    if !action_state.just_pressed(Action::Interact) {
         return;
     }
-    for (door, riddle_info) in doors.iter_mut() {
+    for (door, iid, riddle_info) in doors.iter_mut() {
         if rapier_context.single().intersection_pair(player, door) != Some(true) {
             continue;
         };
-        if answered_riddles.ids.contains(&riddle_info.question) {
-            keyboard_input.reset(KeyCode::Space);
+        if answered_riddles.contains(iid) {
+            action_state.reset(Action::Interact);
             *current_level = LevelSelection::iid(riddle_info.next_level.clone());
             next_state.set(GameState::LevelLoading);
             return;
@@ -173,86 +552,271 @@ fn touch_door_system(
     }
 }
 
+/// Turns typed characters and the riddle actions into riddle input.
+///
+/// Runs in every state, so the keys pressed before a riddle opens never end up in its answer.
+fn keyboard_riddle_input_system(
+    mut keyboard_input: EventReader<KeyboardInput>,
+    state: Res<State<GameState>>,
+    action_state: Res<ActionState>,
+    mut riddle_input: EventWriter<RiddleInput>,
+) {
+    if *state != GameState::RiddleSolving {
+        keyboard_input.clear();
+        return;
+    }
+    for event in keyboard_input.read() {
+        if !event.state.is_pressed() || event.repeat {
+            continue;
+        }
+        let text = match &event.logical_key {
+            Key::Character(text) => text.as_str(),
+            Key::Space => " ",
+            _ => continue,
+        };
+        let mut characters = text.chars();
+        let (Some(character), None) = (characters.next(), characters.next()) else {
+            continue;
+        };
+        riddle_input.send(RiddleInput::Character(character));
+    }
+    for (action, input) in [
+        (Action::DeleteChar, RiddleInput::Delete),
+        (Action::DeleteNextChar, RiddleInput::DeleteNext),
+        (Action::PreviousSlot, RiddleInput::PreviousSlot),
+        (Action::NextSlot, RiddleInput::NextSlot),
+        (Action::FirstSlot, RiddleInput::FirstSlot),
+        (Action::LastSlot, RiddleInput::LastSlot),
+        (Action::Confirm, RiddleInput::Submit),
+        (Action::Cancel, RiddleInput::Close),
+    ] {
+        if action_state.just_pressed(action) {
+            riddle_input.send(input);
+        }
+    }
+}
+
+fn keypad_system(
+    keys: Query<(&Interaction, &KeypadKey), Changed<Interaction>>,
+    slots: Query<(&Interaction, &Answer), Changed<Interaction>>,
+    mut riddle_input: EventWriter<RiddleInput>,
+) {
+    for (interaction, key) in keys.iter() {
+        if *interaction == Interaction::Pressed {
+            riddle_input.send(key.0);
+        }
+    }
+    for (interaction, answer) in slots.iter() {
+        if *interaction == Interaction::Pressed {
+            riddle_input.send(RiddleInput::Focus(answer.position));
+        }
+    }
+}
+
 fn answering_riddle_system(
-    mut input: EventReader<KeyboardInput>,
+    mut riddle_input: EventReader<RiddleInput>,
     mut container_info: Query<(&mut AnswerContainer, &InheritedVisibility)>,
     mut answer_nodes: Query<(&mut Text, &InheritedVisibility, &Answer)>,
 ) {
-    for character in input.read() {
-        if !(character.state.is_pressed()
-            && (KeyCode::Digit0..=KeyCode::Digit9).contains(&character.key_code))
-        {
+    for input in riddle_input.read() {
+        let RiddleInput::Character(character) = *input else {
             continue;
-        }
+        };
         let (mut container, _) = container_info
             .iter_mut()
             .find(|(_, visibility)| visibility.get())
             .expect("A visible container is expected while this system is running!");
+        if !container.answer_kind.accepts(character) {
+            continue;
+        }
         let (mut answer, _, _) = answer_nodes
             .iter_mut()
             .filter(|(_, visibility, _)| visibility.get())
             .find(|(_, _, answer)| answer.position == container.index)
             .expect("The container is expected to have answer positions and the container's index is always valid!");
-        if let Key::Character(character) = &character.logical_key {
-            answer.0 = character.to_string();
-        }
+        answer.0 = character.to_string();
         container.index = (container.index + 1) % container.answer_length;
     }
 }
 
-fn delete_digit_system(
-    keyboard_input: Res<ButtonInput<KeyCode>>,
-    mut container_info: Query<(&mut AnswerContainer, &InheritedVisibility)>,
+/// Cycles the character in the selected slot through the ones its answer kind accepts.
+fn pick_character_system(
+    action_state: Res<ActionState>,
+    container_info: Query<(&AnswerContainer, &InheritedVisibility)>,
     mut answer_nodes: Query<(&mut Text, &InheritedVisibility, &Answer)>,
 ) {
-    if !keyboard_input.just_pressed(KeyCode::Backspace) {
-        return;
-    }
-    let (mut container, _) = container_info
-        .iter_mut()
+    let step = match (
+        action_state.just_pressed(Action::NextChar),
+        action_state.just_pressed(Action::PreviousChar),
+    ) {
+        (true, false) => 1,
+        (false, true) => -1,
+        _ => return,
+    };
+    let (container, _) = container_info
+        .iter()
         .find(|(_, visibility)| visibility.get())
         .expect("A visible container is expected while this system is running!");
-    if container.index == 0 {
-        container.index = container.answer_length;
-    }
-    container.index -= 1;
     let (mut answer, _, _) = answer_nodes
             .iter_mut()
             .find(|(_, visibility, answer)| visibility.get() && answer.position == container.index)
             .expect("The container is expected to have answer positions and the container's index is always valid!");
-    answer.0 = "_".to_string();
+    let characters = container.answer_kind.picker_characters();
+    let current = characters
+        .iter()
+        .position(|character| answer.0.eq_ignore_ascii_case(&character.to_string()));
+    let next = match (current, step) {
+        (None, 1) => 0,
+        (None, _) => characters.len() - 1,
+        (Some(current), step) => {
+            (current as isize + step).rem_euclid(characters.len() as isize) as usize
+        }
+    };
+    answer.0 = characters[next].to_string();
+}
+
+fn move_caret_system(
+    mut riddle_input: EventReader<RiddleInput>,
+    mut container_info: Query<(&mut AnswerContainer, &InheritedVisibility)>,
+) {
+    for input in riddle_input.read() {
+        let (mut container, _) = container_info
+            .iter_mut()
+            .find(|(_, visibility)| visibility.get())
+            .expect("A visible container is expected while this system is running!");
+        let last = container.answer_length - 1;
+        container.index = match *input {
+            RiddleInput::PreviousSlot => container.index.checked_sub(1).unwrap_or(last),
+            RiddleInput::NextSlot => (container.index + 1) % container.answer_length,
+            RiddleInput::FirstSlot => 0,
+            RiddleInput::LastSlot => last,
+            RiddleInput::Focus(position) => position.min(last),
+            _ => continue,
+        };
+    }
 }
 
+/// Outlines the slot the next character goes into.
+fn caret_system(
+    container_info: Query<(&AnswerContainer, &InheritedVisibility)>,
+    mut answer_nodes: Query<(&mut Outline, &InheritedVisibility, &Answer)>,
+) {
+    let Some((container, _)) = container_info
+        .iter()
+        .find(|(_, visibility)| visibility.get())
+    else {
+        return;
+    };
+    for (mut outline, _, answer) in answer_nodes
+        .iter_mut()
+        .filter(|(_, visibility, _)| visibility.get())
+    {
+        let color = if answer.position == container.index {
+            nodes::CARET_COLOR
+        } else {
+            Color::NONE
+        };
+        if outline.color != color {
+            outline.color = color;
+        }
+    }
+}
+
+fn delete_digit_system(
+    mut riddle_input: EventReader<RiddleInput>,
+    mut container_info: Query<(&mut AnswerContainer, &InheritedVisibility)>,
+    mut answer_nodes: Query<(&mut Text, &InheritedVisibility, &Answer)>,
+) {
+    for input in riddle_input.read() {
+        let (mut container, _) = container_info
+            .iter_mut()
+            .find(|(_, visibility)| visibility.get())
+            .expect("A visible container is expected while this system is running!");
+        match input {
+            RiddleInput::Delete => {
+                if container.index == 0 {
+                    container.index = container.answer_length;
+                }
+                container.index -= 1;
+            }
+            RiddleInput::DeleteNext => {}
+            _ => continue,
+        }
+        let (mut answer, _, _) = answer_nodes
+            .iter_mut()
+            .find(|(_, visibility, answer)| visibility.get() && answer.position == container.index)
+            .expect("The container is expected to have answer positions and the container's index is always valid!");
+        answer.0 = "_".to_string();
+    }
+}
+
+/// The answer in the slots, unless it is incomplete.
+///
+/// Empty slots may only trail an answer shorter than the slots, never sit inside one.
+fn complete_answer<'a>(
+    slots: impl Iterator<Item = (usize, &'a str)>,
+    shortest_answer_length: usize,
+) -> Option<String> {
+    let mut slots = Vec::from_iter(slots);
+    slots.sort_by_key(|(position, _)| *position);
+    let length = slots
+        .iter()
+        .position(|(_, value)| *value == "_")
+        .unwrap_or(slots.len());
+    if length < shortest_answer_length || slots[length..].iter().any(|(_, value)| *value != "_") {
+        return None;
+    }
+    Some(slots[..length].iter().map(|(_, value)| *value).collect())
+}
+
+#[allow(clippy::too_many_arguments)]
 fn correct_answer_system(
     mut commands: Commands,
-    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut riddle_input: EventReader<RiddleInput>,
     mut answered_riddles: ResMut<AnsweredRiddles>,
     mut next_state: ResMut<NextState<GameState>>,
-    mut doors: Query<(&mut RiddleInfo, &mut Sprite, &InheritedVisibility)>,
+    mut wrong_answers: EventWriter<WrongAnswer>,
+    mut doors: Query<(Entity, &EntityIid, &mut RiddleInfo, &mut Sprite)>,
+    riddle_nodes: Query<&Visibility, With<RiddleNode>>,
+    container_info: Query<(&AnswerContainer, &InheritedVisibility)>,
     answer_nodes: Query<(&Text, &InheritedVisibility, &Answer)>,
 ) {
-    if !keyboard_input.any_just_pressed([KeyCode::Enter, KeyCode::NumpadEnter]) {
+    if !riddle_input
+        .read()
+        .any(|input| *input == RiddleInput::Submit)
+    {
         return;
     }
-    let mut answer_nodes = Vec::from_iter(
+    let (container, _) = container_info
+        .iter()
+        .find(|(_, visibility)| visibility.get())
+        .expect("A visible container is expected while this system is running!");
+    let Some(answer) = complete_answer(
         answer_nodes
             .iter()
             .filter(|(_, visibility, _)| visibility.get())
-            .map(|(text, _, pos)| (pos.position, text.0.clone())),
-    );
-    answer_nodes.sort_by_key(|(pos, _)| *pos);
-    let answer = answer_nodes
-        .into_iter()
-        .map(|(_, value)| value)
-        .collect::<String>();
-    let (door, mut sprite, _) = doors
+            .map(|(text, _, answer)| (answer.position, text.0.as_str())),
+        container.shortest_answer_length,
+    ) else {
+        return;
+    };
+    let (door_entity, iid, mut door, mut sprite) = doors
         .iter_mut()
-        .find(|(_, _, visibility)| visibility.get())
+        .find(|(_, _, door, _)| {
+            door.riddle
+                .and_then(|riddle| riddle_nodes.get(riddle).ok())
+                .is_some_and(|visibility| *visibility == Visibility::Visible)
+        })
         .expect("Only one door should be active while answering a riddle!");
-    if answer != door.answer {
+    if !door.is_correct(&answer) {
+        door.attempts += 1;
+        wrong_answers.send(WrongAnswer {
+            door: door_entity,
+            attempts: door.attempts,
+        });
         return;
     }
-    answered_riddles.ids.insert(door.question.clone());
+    answered_riddles.insert(iid);
     commands
         .entity(
             door.riddle
@@ -266,40 +830,47 @@ fn correct_answer_system(
 }
 
 fn clear_input_system(
-    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut riddle_input: EventReader<RiddleInput>,
     mut container_info: Query<(&mut AnswerContainer, &InheritedVisibility)>,
-    mut answer_nodes: Query<(&mut Text, &InheritedVisibility), With<Answer>>,
+    mut answer_nodes: Query<(&mut Text, &InheritedVisibility, &Answer)>,
 ) {
-    if !keyboard_input.any_just_pressed([KeyCode::Enter, KeyCode::NumpadEnter]) {
-        return;
-    }
-    if answer_nodes
-        .iter_mut()
-        .filter(|(_, visibility)| visibility.get())
-        .any(|(text, _)| text.0 == *"_")
+    if !riddle_input
+        .read()
+        .any(|input| *input == RiddleInput::Submit)
     {
         return;
     }
-    if let Some((mut container, _)) = container_info
+    let Some((mut container, _)) = container_info
         .iter_mut()
         .find(|(_, visibility)| visibility.get())
-    {
-        container.index = 0;
+    else {
+        return;
+    };
+    let slots = answer_nodes
+        .iter()
+        .filter(|(_, visibility, _)| visibility.get())
+        .map(|(text, _, answer)| (answer.position, text.0.as_str()));
+    if complete_answer(slots, container.shortest_answer_length).is_none() {
+        return;
     }
+    container.index = 0;
     answer_nodes
         .iter_mut()
-        .filter(|(_, visibility)| visibility.get())
-        .for_each(|(mut text, _)| {
+        .filter(|(_, visibility, _)| visibility.get())
+        .for_each(|(mut text, _, _)| {
             text.0 = "_".to_string();
         });
 }
 
 fn close_riddle_system(
-    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut riddle_input: EventReader<RiddleInput>,
     mut next_state: ResMut<NextState<GameState>>,
     mut riddle_nodes: Query<&mut Visibility, With<RiddleNode>>,
 ) {
-    if !keyboard_input.just_pressed(KeyCode::Escape) {
+    if !riddle_input
+        .read()
+        .any(|input| *input == RiddleInput::Close)
+    {
         return;
     }
     let mut riddle_visibility = riddle_nodes
@@ -309,3 +880,830 @@ fn close_riddle_system(
     *riddle_visibility = Visibility::Hidden;
     next_state.set(GameState::MapExploring);
 }
+
+fn wrong_answer_reaction_system(
+    mut commands: Commands,
+    mut events: EventReader<WrongAnswer>,
+    doors: Query<&RiddleInfo>,
+    mut messages: Query<(&Parent, &mut Text, &mut Visibility), With<WrongAnswerMessage>>,
+) {
+    for event in events.read() {
+        let Some(riddle) = doors.get(event.door).ok().and_then(|door| door.riddle) else {
+            continue;
+        };
+        commands.entity(riddle).insert(WrongAnswerFeedback {
+            timer: Timer::from_seconds(WRONG_ANSWER_FEEDBACK_SECONDS, TimerMode::Once),
+        });
+        let Some((_, mut message, mut visibility)) = messages
+            .iter_mut()
+            .find(|(parent, _, _)| parent.get() == riddle)
+        else {
+            continue;
+        };
+        message.0 = format!("Not quite! Attempts: {}", event.attempts);
+        *visibility = Visibility::Inherited;
+    }
+}
+
+fn wrong_answer_feedback_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut riddle_nodes: Query<(
+        Entity,
+        &mut WrongAnswerFeedback,
+        &mut BackgroundColor,
+        &Children,
+    )>,
+    mut containers: Query<&mut Node, With<AnswerContainer>>,
+) {
+    for (riddle, mut feedback, mut background, children) in riddle_nodes.iter_mut() {
+        feedback.timer.tick(time.delta());
+        let progress = feedback.timer.fraction();
+        background.0 = nodes::WRONG_ANSWER_COLOR.mix(&nodes::RIDDLE_BACKGROUND_COLOR, progress);
+        let offset = (feedback.timer.elapsed_secs() * WRONG_ANSWER_SHAKE_FREQUENCY).sin()
+            * WRONG_ANSWER_SHAKE_AMPLITUDE
+            * (1.0 - progress);
+        let mut containers = containers.iter_many_mut(children);
+        while let Some(mut container) = containers.fetch_next() {
+            container.left = Val::Px(offset);
+        }
+        if feedback.timer.finished() {
+            commands.entity(riddle).remove::<WrongAnswerFeedback>();
+        }
+    }
+}
+
+fn reveal_hint_system(
+    time: Res<Time>,
+    mut doors: Query<&mut RiddleInfo>,
+    riddle_nodes: Query<&Visibility, (With<RiddleNode>, Without<Hint>)>,
+    mut hints: Query<(&Hint, &mut Visibility)>,
+) {
+    for mut door in doors.iter_mut() {
+        let Some(riddle) = door.riddle else {
+            continue;
+        };
+        if riddle_nodes.get(riddle) != Ok(&Visibility::Visible) {
+            continue;
+        }
+        if door.revealed_hints >= door.hints.len() {
+            continue;
+        }
+        let delay_elapsed = door
+            .hint_timer
+            .as_mut()
+            .is_some_and(|timer| timer.tick(time.delta()).just_finished());
+        if !(delay_elapsed || door.hint_earned_by_attempts()) {
+            continue;
+        }
+        if let Some((_, mut visibility)) = hints
+            .iter_mut()
+            .find(|(hint, _)| hint.riddle == riddle && hint.index == door.revealed_hints)
+        {
+            *visibility = Visibility::Inherited;
+        }
+        door.revealed_hints += 1;
+        if let Some(timer) = door.hint_timer.as_mut() {
+            timer.reset();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actions::{update_action_state_system, InputMap};
+
+    fn riddle_app() -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<Font>()
+            .insert_resource(AnsweredRiddles::default());
+        app
+    }
+
+    fn answer_slots(app: &mut App) -> Vec<(usize, Color)> {
+        let mut slots = app
+            .world_mut()
+            .query::<(&Answer, &TextColor)>()
+            .iter(app.world())
+            .map(|(answer, color)| (answer.position, color.0))
+            .collect::<Vec<_>>();
+        slots.sort_by_key(|(position, _)| *position);
+        slots
+    }
+
+    #[test]
+    fn test_init_riddles_system_spawns_a_slot_per_answer_character() {
+        // Given
+        let mut app = riddle_app();
+
+        app.world_mut().spawn((
+            EntityIid::new("door"),
+            RiddleInfo {
+                answers: vec!["12345".to_string()],
+                ..default()
+            },
+            Sprite::default(),
+        ));
+
+        // When
+        app.add_systems(Update, init_riddles_system);
+        app.update();
+
+        // Then
+        let slots = answer_slots(&mut app);
+        assert_eq!(
+            slots
+                .iter()
+                .map(|(position, _)| *position)
+                .collect::<Vec<_>>(),
+            vec![0, 1, 2, 3, 4]
+        );
+        assert_eq!(slots[3].1, DEFAULT_SLOT_PALETTE[0]);
+        let container = app
+            .world_mut()
+            .query::<&AnswerContainer>()
+            .single(app.world());
+        assert_eq!(container.answer_length, 5);
+    }
+
+    #[test]
+    fn test_init_riddles_system_uses_the_door_palette() {
+        // Given
+        let mut app = riddle_app();
+
+        let palette = vec![Color::WHITE, Color::BLACK];
+        app.world_mut().spawn((
+            EntityIid::new("door"),
+            RiddleInfo {
+                answers: vec!["abc".to_string()],
+                slot_colors: palette.clone(),
+                ..default()
+            },
+            Sprite::default(),
+        ));
+
+        // When
+        app.add_systems(Update, init_riddles_system);
+        app.update();
+
+        // Then
+        let colors = answer_slots(&mut app)
+            .into_iter()
+            .map(|(_, color)| color)
+            .collect::<Vec<_>>();
+        assert_eq!(colors, vec![Color::WHITE, Color::BLACK, Color::WHITE]);
+    }
+
+    fn spawn_visible_container(app: &mut App, answer_kind: AnswerKind, answer_length: usize) {
+        app.world_mut()
+            .spawn((
+                AnswerContainer {
+                    index: 0,
+                    answer_length,
+                    shortest_answer_length: answer_length,
+                    answer_kind,
+                },
+                InheritedVisibility::VISIBLE,
+            ))
+            .with_children(|parent| {
+                for position in 0..answer_length {
+                    parent.spawn((
+                        Text("_".to_string()),
+                        InheritedVisibility::VISIBLE,
+                        Answer { position },
+                    ));
+                }
+            });
+    }
+
+    fn press_character(app: &mut App, key_code: KeyCode, character: &str) {
+        app.world_mut().send_event(KeyboardInput {
+            key_code,
+            logical_key: Key::Character(character.into()),
+            state: bevy::input::ButtonState::Pressed,
+            repeat: false,
+            window: Entity::PLACEHOLDER,
+        });
+    }
+
+    fn typed_answer(app: &mut App) -> String {
+        let mut slots = app
+            .world_mut()
+            .query::<(&Text, &Answer)>()
+            .iter(app.world())
+            .map(|(text, answer)| (answer.position, text.0.clone()))
+            .collect::<Vec<_>>();
+        slots.sort_by_key(|(position, _)| *position);
+        slots.into_iter().map(|(_, text)| text).collect()
+    }
+
+    #[test]
+    fn test_answering_riddle_system_accepts_numpad_and_shifted_letters() {
+        // Given
+        let mut app = App::new();
+
+        app.add_event::<KeyboardInput>()
+            .add_event::<RiddleInput>()
+            .insert_resource(State::new(GameState::RiddleSolving))
+            .init_resource::<ActionState>()
+            .add_systems(
+                Update,
+                (keyboard_riddle_input_system, answering_riddle_system).chain(),
+            );
+        spawn_visible_container(&mut app, AnswerKind::Alphanumeric, 2);
+
+        // When
+        press_character(&mut app, KeyCode::Numpad7, "7");
+        press_character(&mut app, KeyCode::KeyQ, "Q");
+        app.update();
+
+        // Then
+        assert_eq!(typed_answer(&mut app), "7Q");
+    }
+
+    #[test]
+    fn test_answering_riddle_system_ignores_characters_of_another_kind() {
+        // Given
+        let mut app = App::new();
+
+        app.add_event::<KeyboardInput>()
+            .add_event::<RiddleInput>()
+            .insert_resource(State::new(GameState::RiddleSolving))
+            .init_resource::<ActionState>()
+            .add_systems(
+                Update,
+                (keyboard_riddle_input_system, answering_riddle_system).chain(),
+            );
+        spawn_visible_container(&mut app, AnswerKind::Letters, 2);
+
+        // When
+        press_character(&mut app, KeyCode::Digit1, "1");
+        press_character(&mut app, KeyCode::KeyA, "a");
+        app.update();
+
+        // Then
+        assert_eq!(typed_answer(&mut app), "a_");
+    }
+
+    #[test]
+    fn test_keyboard_riddle_input_system_types_only_fresh_presses_of_an_open_riddle() {
+        // Given
+        let mut app = App::new();
+
+        app.add_event::<KeyboardInput>()
+            .add_event::<RiddleInput>()
+            .insert_resource(State::new(GameState::MapExploring))
+            .init_resource::<ActionState>()
+            .add_systems(
+                Update,
+                (keyboard_riddle_input_system, answering_riddle_system).chain(),
+            );
+        spawn_visible_container(&mut app, AnswerKind::FreeText, 4);
+        press_character(&mut app, KeyCode::KeyX, "x");
+        app.update();
+
+        // When
+        app.insert_resource(State::new(GameState::RiddleSolving));
+        press_character(&mut app, KeyCode::KeyA, "a");
+        app.world_mut().send_event(KeyboardInput {
+            key_code: KeyCode::KeyA,
+            logical_key: Key::Character("a".into()),
+            state: bevy::input::ButtonState::Pressed,
+            repeat: true,
+            window: Entity::PLACEHOLDER,
+        });
+        app.world_mut().send_event(KeyboardInput {
+            key_code: KeyCode::Space,
+            logical_key: Key::Space,
+            state: bevy::input::ButtonState::Pressed,
+            repeat: false,
+            window: Entity::PLACEHOLDER,
+        });
+        press_character(&mut app, KeyCode::Minus, "_");
+        press_character(&mut app, KeyCode::KeyB, "b");
+        app.update();
+
+        // Then
+        assert_eq!(typed_answer(&mut app), "a b_");
+    }
+
+    #[test]
+    fn test_keypad_feeds_the_same_input_as_the_keyboard() {
+        // Given
+        let mut app = App::new();
+
+        app.add_event::<RiddleInput>().add_systems(
+            Update,
+            (keypad_system, answering_riddle_system, delete_digit_system).chain(),
+        );
+        spawn_visible_container(&mut app, AnswerKind::Digits, 3);
+        let key = |app: &mut App, input: RiddleInput| {
+            app.world_mut()
+                .spawn((KeypadKey(input), Interaction::None))
+                .id()
+        };
+        let one = key(&mut app, RiddleInput::Character('1'));
+        let two = key(&mut app, RiddleInput::Character('2'));
+        let delete = key(&mut app, RiddleInput::Delete);
+        let click = |app: &mut App, key: Entity| {
+            *app.world_mut().get_mut::<Interaction>(key).unwrap() = Interaction::Pressed;
+            app.update();
+            *app.world_mut().get_mut::<Interaction>(key).unwrap() = Interaction::None;
+        };
+
+        // When
+        click(&mut app, one);
+        click(&mut app, two);
+        click(&mut app, delete);
+        click(&mut app, two);
+
+        // Then
+        assert_eq!(typed_answer(&mut app), "12_");
+    }
+
+    #[test]
+    fn test_hashed_answers_are_verified_after_normalization() {
+        // Given
+        let normalization = AnswerNormalization::for_kind(AnswerKind::Letters);
+        let riddle = RiddleInfo {
+            answers: vec![answer::hash_answer(
+                "door",
+                &normalization.normalize("Silver"),
+            )],
+            answers_hashed: true,
+            answer_length: Some(6),
+            shortest_answer_length: Some(6),
+            normalization,
+            ..default()
+        };
+
+        // Then
+        assert!(!riddle.answers[0].contains("silver"));
+        assert!(riddle.is_correct("SILVER"));
+        assert!(!riddle.is_correct("bronze"));
+        assert_eq!(riddle.answer_lengths(), vec![6, 6]);
+    }
+
+    #[test]
+    fn test_letter_answers_are_compared_case_insensitively() {
+        // Given
+        let riddle = RiddleInfo {
+            answers: vec!["silver".to_string()],
+            normalization: AnswerNormalization::for_kind(AnswerKind::Letters),
+            ..default()
+        };
+
+        // Then
+        assert!(riddle.is_correct("SiLvEr"));
+        assert!(!riddle.is_correct("bronze"));
+    }
+
+    #[test]
+    fn test_any_of_the_accepted_answers_is_correct() {
+        // Given
+        let riddle = RiddleInfo {
+            answers: vec!["7".to_string(), "seven".to_string()],
+            normalization: AnswerNormalization::for_kind(AnswerKind::Alphanumeric),
+            ..default()
+        };
+
+        // Then
+        assert!(riddle.is_correct("7"));
+        assert!(riddle.is_correct("Seven"));
+        assert!(!riddle.is_correct("07"));
+    }
+
+    #[test]
+    fn test_selected_normalization_steps_are_applied() {
+        // Given
+        let riddle = RiddleInfo {
+            answers: vec!["7".to_string()],
+            normalization: AnswerNormalization::for_kind(AnswerKind::FreeText)
+                .try_with_step("Trim")
+                .and_then(|normalization| normalization.try_with_step("StripLeadingZeros"))
+                .unwrap(),
+            ..default()
+        };
+
+        // Then
+        assert!(riddle.is_correct(" 007 "));
+        assert!(AnswerNormalization::default()
+            .try_with_step("Reverse")
+            .is_err());
+        assert_eq!(
+            AnswerNormalization::for_kind(AnswerKind::Digits)
+                .try_with_step("StripLeadingZeros")
+                .unwrap()
+                .normalize("000"),
+            "0"
+        );
+    }
+
+    #[test]
+    fn test_gamepad_picks_characters_and_moves_between_slots() {
+        // Given
+        let mut app = App::new();
+
+        app.init_resource::<ButtonInput<KeyCode>>()
+            .init_resource::<InputMap>()
+            .init_resource::<ActionState>()
+            .insert_resource(State::new(GameState::RiddleSolving))
+            .add_event::<KeyboardInput>()
+            .add_event::<RiddleInput>()
+            .add_systems(
+                Update,
+                (
+                    update_action_state_system,
+                    keyboard_riddle_input_system,
+                    pick_character_system,
+                    move_caret_system,
+                )
+                    .chain(),
+            );
+        spawn_visible_container(&mut app, AnswerKind::Digits, 2);
+        let gamepad = app.world_mut().spawn(Gamepad::default()).id();
+        let press = |app: &mut App, button: GamepadButton| {
+            let mut gamepad = app.world_mut().get_mut::<Gamepad>(gamepad).unwrap();
+            gamepad.digital_mut().clear();
+            gamepad.digital_mut().release_all();
+            gamepad.digital_mut().press(button);
+            app.update();
+        };
+
+        // When
+        press(&mut app, GamepadButton::DPadUp);
+        press(&mut app, GamepadButton::DPadUp);
+        press(&mut app, GamepadButton::DPadRight);
+        press(&mut app, GamepadButton::DPadDown);
+
+        // Then
+        assert_eq!(typed_answer(&mut app), "19");
+    }
+
+    #[test]
+    fn test_caret_moves_to_any_slot_and_deletes_forward() {
+        // Given
+        let mut app = App::new();
+
+        app.add_event::<RiddleInput>().add_systems(
+            Update,
+            (
+                answering_riddle_system,
+                delete_digit_system,
+                move_caret_system,
+            )
+                .chain(),
+        );
+        spawn_visible_container(&mut app, AnswerKind::Digits, 4);
+        let send = |app: &mut App, inputs: &[RiddleInput]| {
+            for input in inputs {
+                app.world_mut().send_event(*input);
+                app.update();
+            }
+        };
+        send(
+            &mut app,
+            &[
+                RiddleInput::Character('1'),
+                RiddleInput::Character('2'),
+                RiddleInput::Character('3'),
+                RiddleInput::Character('4'),
+            ],
+        );
+
+        // When
+        send(
+            &mut app,
+            &[
+                RiddleInput::LastSlot,
+                RiddleInput::DeleteNext,
+                RiddleInput::Focus(1),
+                RiddleInput::Character('9'),
+                RiddleInput::FirstSlot,
+                RiddleInput::PreviousSlot,
+                RiddleInput::PreviousSlot,
+                RiddleInput::DeleteNext,
+            ],
+        );
+
+        // Then
+        assert_eq!(typed_answer(&mut app), "19__");
+        let container = app
+            .world_mut()
+            .query::<&AnswerContainer>()
+            .single(app.world());
+        assert_eq!(container.index, 2);
+    }
+
+    #[test]
+    fn test_clear_input_system_keeps_entries_shorter_than_every_answer() {
+        // Given
+        let mut app = App::new();
+
+        let mut input = ButtonInput::<KeyCode>::default();
+        app.insert_resource(ButtonInput::<KeyCode>::default())
+            .add_event::<KeyboardInput>()
+            .add_event::<RiddleInput>()
+            .insert_resource(State::new(GameState::RiddleSolving))
+            .init_resource::<InputMap>()
+            .init_resource::<ActionState>()
+            .add_systems(
+                Update,
+                (
+                    update_action_state_system,
+                    keyboard_riddle_input_system,
+                    answering_riddle_system,
+                    clear_input_system,
+                )
+                    .chain(),
+            );
+        spawn_visible_container(&mut app, AnswerKind::Digits, 3);
+        press_character(&mut app, KeyCode::Digit1, "1");
+        app.update();
+
+        // When
+        input.press(KeyCode::Enter);
+        app.insert_resource(input);
+        app.update();
+
+        // Then
+        assert_eq!(typed_answer(&mut app), "1__");
+    }
+
+    #[test]
+    fn test_wrong_answer_counts_an_attempt_for_the_open_door() {
+        // Given
+        let mut app = App::new();
+
+        let mut input = ButtonInput::<KeyCode>::default();
+        let spawn_door = |app: &mut App, visibility: Visibility| {
+            let riddle = app.world_mut().spawn((RiddleNode, visibility)).id();
+            app.world_mut()
+                .spawn((
+                    EntityIid::new(format!("{visibility:?}")),
+                    RiddleInfo {
+                        answers: vec!["12".to_string()],
+                        riddle: Some(riddle),
+                        ..default()
+                    },
+                    Sprite::default(),
+                ))
+                .id()
+        };
+        let closed_door = spawn_door(&mut app, Visibility::Hidden);
+        let open_door = spawn_door(&mut app, Visibility::Visible);
+        app.insert_resource(ButtonInput::<KeyCode>::default())
+            .init_resource::<AnsweredRiddles>()
+            .init_resource::<NextState<GameState>>()
+            .add_event::<KeyboardInput>()
+            .add_event::<RiddleInput>()
+            .add_event::<WrongAnswer>()
+            .insert_resource(State::new(GameState::RiddleSolving))
+            .init_resource::<InputMap>()
+            .init_resource::<ActionState>()
+            .add_systems(
+                Update,
+                (
+                    update_action_state_system,
+                    keyboard_riddle_input_system,
+                    answering_riddle_system,
+                    correct_answer_system,
+                )
+                    .chain(),
+            );
+        spawn_visible_container(&mut app, AnswerKind::Digits, 2);
+        press_character(&mut app, KeyCode::Digit2, "2");
+        press_character(&mut app, KeyCode::Digit1, "1");
+        app.update();
+
+        // When
+        input.press(KeyCode::Enter);
+        app.insert_resource(input);
+        app.update();
+
+        // Then
+        assert_eq!(
+            app.world().get::<RiddleInfo>(open_door).unwrap().attempts,
+            1
+        );
+        assert_eq!(
+            app.world().get::<RiddleInfo>(closed_door).unwrap().attempts,
+            0
+        );
+        let events = app.world().resource::<Events<WrongAnswer>>();
+        let event = events.iter_current_update_events().next().unwrap();
+        assert_eq!(event.door, open_door);
+        assert_eq!(event.attempts, 1);
+    }
+
+    fn spawn_door_with_hints(app: &mut App, door: RiddleInfo) -> (Entity, Vec<Entity>) {
+        let riddle = app
+            .world_mut()
+            .spawn((RiddleNode, Visibility::Visible))
+            .id();
+        let hints = (0..door.hints.len())
+            .map(|index| {
+                app.world_mut()
+                    .spawn((Hint { riddle, index }, Visibility::Hidden))
+                    .id()
+            })
+            .collect();
+        let door = app
+            .world_mut()
+            .spawn(RiddleInfo {
+                riddle: Some(riddle),
+                ..door
+            })
+            .id();
+        (door, hints)
+    }
+
+    #[test]
+    fn test_hints_are_revealed_one_by_one_after_the_delay() {
+        // Given
+        let mut app = App::new();
+
+        app.init_resource::<Time>()
+            .add_systems(Update, reveal_hint_system);
+        let (_, hints) = spawn_door_with_hints(
+            &mut app,
+            RiddleInfo {
+                hints: vec!["first".to_string(), "second".to_string()],
+                hint_timer: Some(Timer::from_seconds(10.0, TimerMode::Repeating)),
+                ..default()
+            },
+        );
+
+        // When
+        app.world_mut()
+            .resource_mut::<Time>()
+            .advance_by(std::time::Duration::from_secs(10));
+        app.update();
+
+        // Then
+        assert_eq!(
+            app.world().get::<Visibility>(hints[0]),
+            Some(&Visibility::Inherited)
+        );
+        assert_eq!(
+            app.world().get::<Visibility>(hints[1]),
+            Some(&Visibility::Hidden)
+        );
+    }
+
+    #[test]
+    fn test_correct_answer_system_ignores_answers_with_an_empty_slot_inside() {
+        // Given
+        let mut app = App::new();
+
+        let riddle = app
+            .world_mut()
+            .spawn((RiddleNode, Visibility::Visible))
+            .id();
+        let door = app
+            .world_mut()
+            .spawn((
+                EntityIid::new("door"),
+                RiddleInfo {
+                    answers: vec!["12".to_string()],
+                    riddle: Some(riddle),
+                    ..default()
+                },
+                Sprite::default(),
+            ))
+            .id();
+        app.init_resource::<AnsweredRiddles>()
+            .init_resource::<NextState<GameState>>()
+            .add_event::<RiddleInput>()
+            .add_event::<WrongAnswer>()
+            .add_systems(Update, (correct_answer_system, clear_input_system).chain());
+        spawn_visible_container(&mut app, AnswerKind::Digits, 3);
+        let mut slots = app.world_mut().query::<(&mut Text, &Answer)>();
+        for (mut text, answer) in slots.iter_mut(app.world_mut()) {
+            text.0 = ["1", "_", "2"][answer.position].to_string();
+        }
+
+        // When
+        app.world_mut().send_event(RiddleInput::Submit);
+        app.update();
+
+        // Then
+        assert_eq!(app.world().get::<RiddleInfo>(door).unwrap().attempts, 0);
+        assert!(!app
+            .world()
+            .resource::<AnsweredRiddles>()
+            .contains(&EntityIid::new("door")));
+        assert_eq!(typed_answer(&mut app), "1_2");
+    }
+
+    #[test]
+    fn test_hints_are_revealed_after_wrong_attempts() {
+        // Given
+        let mut app = App::new();
+
+        app.init_resource::<Time>()
+            .add_systems(Update, reveal_hint_system);
+        let (door, hints) = spawn_door_with_hints(
+            &mut app,
+            RiddleInfo {
+                hints: vec!["first".to_string(), "second".to_string()],
+                hint_attempts: Some(2),
+                attempts: 1,
+                ..default()
+            },
+        );
+        app.update();
+        assert_eq!(
+            app.world().get::<Visibility>(hints[0]),
+            Some(&Visibility::Hidden)
+        );
+
+        // When
+        app.world_mut()
+            .get_mut::<RiddleInfo>(door)
+            .unwrap()
+            .attempts = 4;
+        app.update();
+        app.update();
+
+        // Then
+        assert!(hints
+            .iter()
+            .all(|hint| { app.world().get::<Visibility>(*hint) == Some(&Visibility::Inherited) }));
+    }
+
+    #[test]
+    fn test_answered_riddles_are_tracked_per_door_not_per_question() {
+        // Given
+        let mut app = riddle_app();
+
+        app.world_mut()
+            .resource_mut::<AnsweredRiddles>()
+            .insert(&EntityIid::new("solved"));
+        let mut spawn_door = |iid: &str| {
+            app.world_mut()
+                .spawn((
+                    EntityIid::new(iid.to_string()),
+                    RiddleInfo {
+                        question: "You win?".to_string(),
+                        answers: vec!["1".to_string()],
+                        ..default()
+                    },
+                    Sprite::default(),
+                ))
+                .id()
+        };
+        let solved = spawn_door("solved");
+        let unsolved = spawn_door("unsolved");
+
+        // When
+        app.add_systems(Update, init_riddles_system);
+        app.update();
+
+        // Then
+        assert!(app
+            .world()
+            .get::<RiddleInfo>(solved)
+            .unwrap()
+            .riddle
+            .is_none());
+        assert!(app
+            .world()
+            .get::<RiddleInfo>(unsolved)
+            .unwrap()
+            .riddle
+            .is_some());
+    }
+
+    #[test]
+    fn test_legacy_question_ids_are_migrated_to_door_iids() {
+        // Given
+        let mut answered_riddles = AnsweredRiddles::default();
+        answered_riddles.ids.insert("Shared question".to_string());
+        answered_riddles
+            .ids
+            .insert("Question of another level".to_string());
+        let first = EntityIid::new("first");
+        let second = EntityIid::new("second");
+        let other = EntityIid::new("other");
+
+        // When
+        answered_riddles.migrate_legacy_ids(
+            [
+                (&first, "Shared question"),
+                (&second, "Shared question"),
+                (&other, "Unsolved question"),
+            ]
+            .into_iter(),
+        );
+
+        // Then
+        assert!(answered_riddles.contains(&first));
+        assert!(answered_riddles.contains(&second));
+        assert!(!answered_riddles.contains(&other));
+        assert!(!answered_riddles.ids.contains("Shared question"));
+        assert!(answered_riddles.ids.contains("Question of another level"));
+    }
+}
@@ -0,0 +1,115 @@
+use sha2::{Digest, Sha256};
+
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AnswerKind {
+    #[default]
+    Digits,
+    Letters,
+    Alphanumeric,
+    FreeText,
+}
+
+impl AnswerKind {
+    pub fn accepts(&self, character: char) -> bool {
+        match self {
+            AnswerKind::Digits => character.is_ascii_digit(),
+            AnswerKind::Letters => character.is_alphabetic(),
+            AnswerKind::Alphanumeric => character.is_alphanumeric(),
+            // An underscore marks an empty slot, so it cannot be part of an answer.
+            AnswerKind::FreeText => !character.is_control() && character != '_',
+        }
+    }
+
+    /// The characters a gamepad cycles through, since it cannot type.
+    pub fn picker_characters(&self) -> Vec<char> {
+        let digits = '0'..='9';
+        let letters = 'A'..='Z';
+        match self {
+            AnswerKind::Digits => Vec::from_iter(digits),
+            AnswerKind::Letters => Vec::from_iter(letters),
+            AnswerKind::Alphanumeric => Vec::from_iter(digits.chain(letters)),
+            AnswerKind::FreeText => Vec::from_iter(letters.chain(digits).chain(" .,'!?-".chars())),
+        }
+    }
+}
+
+impl TryFrom<&str> for AnswerKind {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "Digits" => Ok(AnswerKind::Digits),
+            "Letters" => Ok(AnswerKind::Letters),
+            "Alphanumeric" => Ok(AnswerKind::Alphanumeric),
+            "FreeText" => Ok(AnswerKind::FreeText),
+            _ => Err(format!("unknown answer kind {value}")),
+        }
+    }
+}
+
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AnswerNormalization {
+    trim: bool,
+    case_fold: bool,
+    strip_leading_zeros: bool,
+}
+
+impl AnswerNormalization {
+    /// Every kind but digits compares case-insensitively, whatever the door selects.
+    pub fn for_kind(answer_kind: AnswerKind) -> Self {
+        Self {
+            case_fold: answer_kind != AnswerKind::Digits,
+            ..Self::default()
+        }
+    }
+
+    pub fn try_with_step(mut self, step: &str) -> Result<Self, String> {
+        match step {
+            "Trim" => self.trim = true,
+            "CaseFold" => self.case_fold = true,
+            "StripLeadingZeros" => self.strip_leading_zeros = true,
+            _ => return Err(format!("unknown answer normalization {step}")),
+        }
+        Ok(self)
+    }
+
+    pub fn normalize(&self, value: &str) -> String {
+        let mut value = value.to_string();
+        if self.trim {
+            value = value.trim().to_string();
+        }
+        if self.case_fold {
+            value = value.to_lowercase();
+        }
+        if self.strip_leading_zeros {
+            let stripped = value.trim_start_matches('0');
+            value = if stripped.is_empty() && !value.is_empty() {
+                "0".to_string()
+            } else {
+                stripped.to_string()
+            };
+        }
+        value
+    }
+
+    pub fn matches(&self, input: &str, answer: &str) -> bool {
+        self.normalize(input) == self.normalize(answer)
+    }
+}
+
+/// Hashes a normalized answer into the `salt$hex` form stored in the map, where the hex
+/// digits are the SHA-256 of the salt followed by the answer.
+pub fn hash_answer(salt: &str, normalized_answer: &str) -> String {
+    let digest = Sha256::digest(format!("{salt}{normalized_answer}"));
+    let hex = digest
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<String>();
+    format!("{salt}${hex}")
+}
+
+pub fn matches_hash(normalized_input: &str, hashed_answer: &str) -> bool {
+    hashed_answer
+        .split_once('$')
+        .is_some_and(|(salt, _)| hash_answer(salt, normalized_input) == hashed_answer)
+}
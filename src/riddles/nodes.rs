@@ -1,5 +1,10 @@
+use crate::menu::nodes::BUTTON_COLOR;
 use bevy::prelude::*;
 
+pub const RIDDLE_BACKGROUND_COLOR: Color = Color::srgb(0.5, 0.5, 0.85);
+pub const WRONG_ANSWER_COLOR: Color = Color::srgb(0.85, 0.2, 0.2);
+pub const CARET_COLOR: Color = Color::WHITE;
+
 pub fn root_node() -> (Node, BackgroundColor, Visibility) {
     (
         Node {
@@ -11,7 +16,7 @@ pub fn root_node() -> (Node, BackgroundColor, Visibility) {
             height: Val::Percent(100.0),
             ..default()
         },
-        BackgroundColor(Color::srgb(0.5, 0.5, 0.85)),
+        BackgroundColor(RIDDLE_BACKGROUND_COLOR),
         Visibility::Hidden,
     )
 }
@@ -39,14 +44,15 @@ pub fn answer_container() -> (Node, BackgroundColor) {
     (
         Node {
             justify_content: JustifyContent::SpaceAround,
-            width: Val::Percent(30.0),
+            min_width: Val::Percent(30.0),
+            column_gap: Val::Px(20.0),
             ..default()
         },
         BackgroundColor(Color::NONE),
     )
 }
 
-pub fn answer_position(asset_server: &Res<AssetServer>) -> (Text, TextFont) {
+pub fn answer_position(asset_server: &Res<AssetServer>) -> (Text, TextFont, Outline, Interaction) {
     (
         Text("_".to_string()),
         TextFont {
@@ -54,5 +60,88 @@ pub fn answer_position(asset_server: &Res<AssetServer>) -> (Text, TextFont) {
             font_size: 60.0,
             ..default()
         },
+        Outline::new(Val::Px(3.0), Val::Px(4.0), Color::NONE),
+        Interaction::default(),
+    )
+}
+
+pub fn keypad() -> Node {
+    Node {
+        display: Display::Flex,
+        flex_wrap: FlexWrap::Wrap,
+        justify_content: JustifyContent::Center,
+        max_width: Val::Percent(60.0),
+        column_gap: Val::Px(8.0),
+        row_gap: Val::Px(8.0),
+        ..default()
+    }
+}
+
+pub fn keypad_key() -> (Button, Node, BackgroundColor) {
+    (
+        Button,
+        Node {
+            min_width: Val::Px(50.0),
+            padding: UiRect::axes(Val::Px(10.0), Val::Px(6.0)),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        BackgroundColor(BUTTON_COLOR),
+    )
+}
+
+pub fn keypad_key_text(asset_server: &Res<AssetServer>, label: &str) -> (Text, TextFont) {
+    (
+        Text(label.to_string()),
+        TextFont {
+            font: asset_server.load("fonts/MontserratAlternates-MediumItalic.ttf"),
+            font_size: 25.0,
+            ..default()
+        },
+    )
+}
+
+pub fn wrong_answer_text(
+    asset_server: &Res<AssetServer>,
+) -> (Text, TextColor, TextFont, Visibility) {
+    (
+        Text("".to_string()),
+        TextColor(WRONG_ANSWER_COLOR),
+        TextFont {
+            font: asset_server.load("fonts/MontserratAlternates-MediumItalic.ttf"),
+            font_size: 30.0,
+            ..default()
+        },
+        Visibility::Hidden,
+    )
+}
+
+pub fn hint_container() -> Node {
+    Node {
+        display: Display::Flex,
+        flex_direction: FlexDirection::Column,
+        align_items: AlignItems::Center,
+        row_gap: Val::Px(10.0),
+        ..default()
+    }
+}
+
+pub fn hint_text(
+    asset_server: &Res<AssetServer>,
+    hint: &str,
+) -> (Text, TextColor, TextFont, TextLayout, Visibility) {
+    (
+        Text(hint.to_string()),
+        TextColor(Color::srgb(1.0, 1.0, 0.6)),
+        TextFont {
+            font: asset_server.load("fonts/MontserratAlternates-MediumItalic.ttf"),
+            font_size: 30.0,
+            ..default()
+        },
+        TextLayout {
+            justify: JustifyText::Center,
+            ..Default::default()
+        },
+        Visibility::Hidden,
     )
 }
@@ -0,0 +1,540 @@
+use crate::{map::STARTING_LEVEL, player::Player, riddles::AnsweredRiddles, GameState};
+use bevy::{ecs::system::SystemParam, prelude::*};
+use bevy_ecs_ldtk::prelude::*;
+use serde::{Deserialize, Serialize};
+use slot_select::SlotSelectPlugin;
+use std::{fs, path::PathBuf};
+
+mod slot_select;
+
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(SlotSelectPlugin)
+            .insert_resource(SaveSlots::default())
+            .init_resource::<CurrentSlot>()
+            .init_resource::<PlayTime>()
+            .init_resource::<PendingPlayerPosition>()
+            .add_systems(Startup, import_legacy_save_system)
+            .add_systems(
+                OnExit(GameState::LevelLoading),
+                restore_player_position_system,
+            )
+            .add_systems(OnEnter(GameState::Paused), save_now_system)
+            .add_systems(
+                OnEnter(GameState::MainMenu),
+                (save_now_system, leave_slot_system).chain(),
+            )
+            .add_systems(
+                Update,
+                (play_time_system, save_progress_system).chain().run_if(
+                    in_state(GameState::MapExploring).or(in_state(GameState::RiddleSolving)),
+                ),
+            )
+            .add_systems(Last, save_on_exit_system);
+    }
+}
+
+const SAVE_DIRECTORY: &str = "puzzle-up";
+const SLOTS_DIRECTORY: &str = "slots";
+const LEGACY_SAVE_FILE_NAME: &str = "save.ron";
+pub(crate) const MAX_SLOT_NAME_LENGTH: usize = 24;
+
+type SlotId = u32;
+
+#[derive(Resource)]
+pub struct SaveSlots {
+    directory: Option<PathBuf>,
+}
+
+impl Default for SaveSlots {
+    fn default() -> Self {
+        Self {
+            directory: dirs::data_dir().map(|dir| dir.join(SAVE_DIRECTORY).join(SLOTS_DIRECTORY)),
+        }
+    }
+}
+
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct SaveData {
+    name: String,
+    answered_riddles: Vec<String>,
+    level: Option<String>,
+    level_name: Option<String>,
+    player_position: Option<(f32, f32)>,
+    play_time: f32,
+}
+
+impl SaveData {
+    fn summary(&self) -> String {
+        let play_time = self.play_time as u64;
+        let level = match (&self.level_name, &self.level) {
+            (Some(level_name), _) => level_name.as_str(),
+            (None, Some(_)) => "Unknown level",
+            (None, None) => "Not started",
+        };
+        format!(
+            "{} - {} riddles solved - {}:{:02}:{:02}",
+            level,
+            self.answered_riddles.len(),
+            play_time / 3600,
+            play_time / 60 % 60,
+            play_time % 60
+        )
+    }
+}
+
+impl SaveSlots {
+    fn slot_path(&self, id: SlotId) -> Option<PathBuf> {
+        Some(self.directory.as_ref()?.join(format!("slot-{id}.ron")))
+    }
+
+    fn list(&self) -> Vec<(SlotId, SaveData)> {
+        let Some(entries) = self
+            .directory
+            .as_ref()
+            .and_then(|directory| fs::read_dir(directory).ok())
+        else {
+            return Vec::new();
+        };
+        let mut slots = Vec::from_iter(entries.flatten().filter_map(|entry| {
+            let id = entry
+                .file_name()
+                .to_str()?
+                .strip_prefix("slot-")?
+                .strip_suffix(".ron")?
+                .parse()
+                .ok()?;
+            Some((id, self.read(id)?))
+        }));
+        slots.sort_by_key(|(id, _)| *id);
+        slots
+    }
+
+    fn read(&self, id: SlotId) -> Option<SaveData> {
+        let path = self.slot_path(id)?;
+        let content = fs::read_to_string(&path).ok()?;
+        ron::from_str(&content)
+            .inspect_err(|error| warn!("Ignoring the unreadable save {path:?}: {error}"))
+            .ok()
+    }
+
+    fn write(&self, id: SlotId, data: &SaveData) {
+        let Some(path) = self.slot_path(id) else {
+            return;
+        };
+        let content = ron::ser::to_string_pretty(data, ron::ser::PrettyConfig::default())
+            .expect("The save data is always serializable!");
+        if let Err(error) = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(&path, content))
+        {
+            warn!("Could not write the save {path:?}: {error}");
+        }
+    }
+
+    fn rename(&self, id: SlotId, name: &str) {
+        let Some(data) = self.read(id) else {
+            return;
+        };
+        self.write(
+            id,
+            &SaveData {
+                name: name.to_string(),
+                ..data
+            },
+        );
+    }
+
+    fn delete(&self, id: SlotId) {
+        let Some(path) = self.slot_path(id) else {
+            return;
+        };
+        if let Err(error) = fs::remove_file(&path) {
+            warn!("Could not delete the save {path:?}: {error}");
+        }
+    }
+
+    fn free_id(&self) -> SlotId {
+        let slots = self.list();
+        (1..)
+            .find(|id| slots.iter().all(|(slot, _)| slot != id))
+            .expect("There is always a free slot id!")
+    }
+
+    fn create(&self) -> SlotId {
+        let id = self.free_id();
+        self.write(
+            id,
+            &SaveData {
+                name: format!("Slot {id}"),
+                ..default()
+            },
+        );
+        id
+    }
+
+    fn copy(&self, id: SlotId) -> Option<SlotId> {
+        let data = self.read(id)?;
+        let copy = self.free_id();
+        self.write(
+            copy,
+            &SaveData {
+                name: format!("{} (copy)", data.name),
+                ..data
+            },
+        );
+        Some(copy)
+    }
+
+    /// Progress saved before slots existed becomes a slot of its own.
+    fn import_legacy_save(&self) {
+        let Some(legacy_path) = self
+            .directory
+            .as_ref()
+            .and_then(|directory| directory.parent())
+            .map(|directory| directory.join(LEGACY_SAVE_FILE_NAME))
+        else {
+            return;
+        };
+        let Some(data) = fs::read_to_string(&legacy_path)
+            .ok()
+            .and_then(|content| ron::from_str::<SaveData>(&content).ok())
+        else {
+            return;
+        };
+        let id = self.free_id();
+        self.write(
+            id,
+            &SaveData {
+                name: format!("Slot {id}"),
+                ..data
+            },
+        );
+        if let Err(error) = fs::rename(&legacy_path, legacy_path.with_extension("ron.imported")) {
+            warn!("Could not retire the imported save {legacy_path:?}: {error}");
+        }
+    }
+}
+
+#[derive(Default, Resource)]
+pub(crate) struct CurrentSlot(Option<SlotId>);
+
+#[derive(Default, Resource)]
+struct PlayTime(f32);
+
+/// Where the player stood when the game was saved, applied once their level is loaded.
+#[derive(Default, Resource)]
+struct PendingPlayerPosition {
+    level: Option<String>,
+    position: Option<Vec2>,
+}
+
+/// Starts a new game in a freshly created slot.
+pub fn new_game(commands: &mut Commands, save_slots: &SaveSlots) {
+    let id = save_slots.create();
+    if let Some(data) = save_slots.read(id) {
+        load_slot(commands, id, data);
+    }
+}
+
+fn load_slot(commands: &mut Commands, id: SlotId, data: SaveData) {
+    load_detached(commands, data);
+    commands.insert_resource(CurrentSlot(Some(id)));
+}
+
+/// The progress of the slot being played, if any.
+pub(crate) fn current_save_data(
+    save_slots: &SaveSlots,
+    current_slot: &CurrentSlot,
+) -> Option<SaveData> {
+    save_slots.read(current_slot.0?)
+}
+
+/// Loads progress without a slot to save it back to, as replays do.
+pub(crate) fn load_detached(commands: &mut Commands, data: SaveData) {
+    let level = data.level.unwrap_or_else(|| STARTING_LEVEL.to_string());
+    commands.insert_resource(CurrentSlot(None));
+    commands.insert_resource(PlayTime(data.play_time));
+    commands.insert_resource(AnsweredRiddles::from_iter(data.answered_riddles));
+    commands.insert_resource(LevelSelection::iid(level.clone()));
+    commands.insert_resource(PendingPlayerPosition {
+        level: Some(level),
+        position: data.player_position.map(Vec2::from),
+    });
+}
+
+fn import_legacy_save_system(save_slots: Res<SaveSlots>) {
+    save_slots.import_legacy_save();
+}
+
+fn restore_player_position_system(
+    current_level: Res<LevelSelection>,
+    mut pending_position: ResMut<PendingPlayerPosition>,
+    mut player_info: Query<&mut Transform, With<Player>>,
+) {
+    let PendingPlayerPosition {
+        level: Some(level),
+        position: Some(position),
+    } = std::mem::take(&mut *pending_position)
+    else {
+        return;
+    };
+    if *current_level != LevelSelection::iid(level) {
+        return;
+    }
+    let Ok(mut transform) = player_info.get_single_mut() else {
+        return;
+    };
+    transform.translation = position.extend(transform.translation.z);
+}
+
+fn play_time_system(time: Res<Time>, mut play_time: ResMut<PlayTime>) {
+    play_time.0 += time.delta_secs();
+}
+
+/// Everything that goes into a save of the current slot.
+#[derive(SystemParam)]
+struct Progress<'w, 's> {
+    save_slots: Res<'w, SaveSlots>,
+    current_slot: Res<'w, CurrentSlot>,
+    play_time: Res<'w, PlayTime>,
+    answered_riddles: Res<'w, AnsweredRiddles>,
+    current_level: Res<'w, LevelSelection>,
+    ldtk_projects: Query<'w, 's, &'static LdtkProjectHandle>,
+    ldtk_project_assets: Res<'w, Assets<LdtkProject>>,
+    player_info: Query<'w, 's, (&'static Transform, &'static Parent), With<Player>>,
+    ancestors: Query<'w, 's, (Option<&'static Parent>, Option<&'static LevelIid>)>,
+}
+
+impl Progress<'_, '_> {
+    fn save(&self) {
+        let Some(id) = self.current_slot.0 else {
+            return;
+        };
+        let LevelSelection::Iid(level) = &*self.current_level else {
+            return;
+        };
+        let Some(mut data) = self.save_slots.read(id) else {
+            return;
+        };
+        // The player still stands in the previous level on the frame a door changes it.
+        let player_position = self
+            .player_info
+            .get_single()
+            .ok()
+            .and_then(|(transform, parent)| {
+                let mut ancestor = Some(parent.get());
+                while let Some((parent, level_iid)) =
+                    ancestor.and_then(|entity| self.ancestors.get(entity).ok())
+                {
+                    if let Some(level_iid) = level_iid {
+                        return (level_iid == level)
+                            .then(|| transform.translation.truncate().into());
+                    }
+                    ancestor = parent.map(Parent::get);
+                }
+                None
+            });
+        let mut riddles = Vec::from_iter(self.answered_riddles.ids().map(String::from));
+        riddles.sort();
+        data.answered_riddles = riddles;
+        data.level = Some(level.to_string());
+        data.level_name = self
+            .ldtk_projects
+            .get_single()
+            .ok()
+            .and_then(|handle| self.ldtk_project_assets.get(handle))
+            .and_then(|project| project.get_raw_level_by_iid(level.get()))
+            .map(|level| level.identifier.clone());
+        data.player_position = player_position;
+        data.play_time = self.play_time.0;
+        self.save_slots.write(id, &data);
+    }
+}
+
+/// Saves whenever a riddle gets solved or the level changes.
+fn save_progress_system(progress: Progress) {
+    if progress.answered_riddles.is_changed() || progress.current_level.is_changed() {
+        progress.save();
+    }
+}
+
+/// Saves when the game gets paused or left for the main menu, so no play time gets lost.
+fn save_now_system(progress: Progress) {
+    progress.save();
+}
+
+fn save_on_exit_system(progress: Progress, mut exit: EventReader<AppExit>) {
+    if exit.read().next().is_some() {
+        progress.save();
+    }
+}
+
+/// The main menu leaves the slot, so nothing saves over it until a slot is played again.
+fn leave_slot_system(mut current_slot: ResMut<CurrentSlot>) {
+    current_slot.0 = None;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_save_slots(name: &str) -> SaveSlots {
+        let directory = std::env::temp_dir()
+            .join(format!("puzzle-up-{}", std::process::id()))
+            .join(name)
+            .join(SLOTS_DIRECTORY);
+        let _ = fs::remove_dir_all(directory.parent().unwrap());
+        SaveSlots {
+            directory: Some(directory),
+        }
+    }
+
+    #[test]
+    fn test_save_slots_can_be_created_copied_and_deleted() {
+        // Given
+        let save_slots = test_save_slots("slots");
+
+        // When
+        let first = save_slots.create();
+        let second = save_slots.copy(first).unwrap();
+        let third = save_slots.create();
+        save_slots.delete(second);
+
+        // Then
+        let names = Vec::from_iter(
+            save_slots
+                .list()
+                .into_iter()
+                .map(|(id, data)| (id, data.name)),
+        );
+        assert_eq!(
+            names,
+            vec![(first, "Slot 1".to_string()), (third, "Slot 3".to_string())]
+        );
+        assert_eq!(save_slots.create(), 2);
+    }
+
+    #[test]
+    fn test_save_slots_can_be_renamed() {
+        // Given
+        let save_slots = test_save_slots("rename");
+        let id = save_slots.create();
+
+        // When
+        save_slots.rename(id, "Speedrun");
+
+        // Then
+        assert_eq!(save_slots.read(id).unwrap().name, "Speedrun");
+    }
+
+    #[test]
+    fn test_legacy_save_is_imported_as_a_slot() {
+        // Given
+        let save_slots = test_save_slots("legacy");
+        let legacy_path = save_slots
+            .directory
+            .as_ref()
+            .unwrap()
+            .parent()
+            .unwrap()
+            .join(LEGACY_SAVE_FILE_NAME);
+        fs::create_dir_all(legacy_path.parent().unwrap()).unwrap();
+        fs::write(
+            &legacy_path,
+            "(answered_riddles: [\"door\"], level: Some(\"level\"), player_position: None)",
+        )
+        .unwrap();
+
+        // When
+        save_slots.import_legacy_save();
+
+        // Then
+        let slots = save_slots.list();
+        assert_eq!(slots.len(), 1);
+        assert_eq!(slots[0].1.name, "Slot 1");
+        assert_eq!(slots[0].1.answered_riddles, vec!["door".to_string()]);
+        assert!(!legacy_path.exists());
+    }
+
+    #[test]
+    fn test_save_progress_system_saves_the_current_slot_on_level_change() {
+        // Given
+        let mut app = App::new();
+
+        let save_slots = test_save_slots("level_change");
+        let id = save_slots.create();
+        app.insert_resource(save_slots)
+            .insert_resource(CurrentSlot(Some(id)))
+            .insert_resource(PlayTime(61.0))
+            .insert_resource(AnsweredRiddles::from_iter(["door".to_string()]))
+            .insert_resource(LevelSelection::iid("first"))
+            .init_resource::<Assets<LdtkProject>>()
+            .add_systems(Update, save_progress_system);
+        let level = app.world_mut().spawn(LevelIid::new("first")).id();
+        app.world_mut()
+            .spawn((Player, Transform::from_xyz(5.0, 5.0, 0.0)))
+            .set_parent(level);
+        app.update();
+        let data = app.world().resource::<SaveSlots>().read(id).unwrap();
+        assert_eq!(data.player_position, Some((5.0, 5.0)));
+
+        // When
+        app.insert_resource(LevelSelection::iid("second"));
+        app.update();
+
+        // Then
+        let data = app.world().resource::<SaveSlots>().read(id).unwrap();
+        assert_eq!(
+            data,
+            SaveData {
+                name: "Slot 1".to_string(),
+                answered_riddles: vec!["door".to_string()],
+                level: Some("second".to_string()),
+                level_name: None,
+                player_position: None,
+                play_time: 61.0,
+            }
+        );
+        assert_eq!(data.summary(), "Unknown level - 1 riddles solved - 0:01:01");
+    }
+
+    #[test]
+    fn test_play_time_is_saved_when_the_game_exits() {
+        // Given
+        let mut app = App::new();
+
+        let save_slots = test_save_slots("exit");
+        let id = save_slots.create();
+        app.insert_resource(save_slots)
+            .insert_resource(CurrentSlot(Some(id)))
+            .insert_resource(PlayTime(90.0))
+            .init_resource::<AnsweredRiddles>()
+            .insert_resource(LevelSelection::iid("first"))
+            .init_resource::<Assets<LdtkProject>>()
+            .add_event::<AppExit>()
+            .add_systems(Last, save_on_exit_system);
+        app.update();
+        assert_eq!(
+            app.world()
+                .resource::<SaveSlots>()
+                .read(id)
+                .unwrap()
+                .play_time,
+            0.0
+        );
+
+        // When
+        app.world_mut().send_event(AppExit::Success);
+        app.update();
+
+        // Then
+        let data = app.world().resource::<SaveSlots>().read(id).unwrap();
+        assert_eq!(data.play_time, 90.0);
+        assert_eq!(data.level, Some("first".to_string()));
+    }
+}
@@ -0,0 +1,261 @@
+use super::{load_slot, SaveSlots, SlotId, MAX_SLOT_NAME_LENGTH};
+use crate::GameState;
+use bevy::{
+    input::keyboard::{Key, KeyboardInput},
+    prelude::*,
+};
+
+pub struct SlotSelectPlugin;
+
+impl Plugin for SlotSelectPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SlotEdit>()
+            .add_systems(OnEnter(GameState::SlotSelecting), slot_screen_setup_system)
+            .add_systems(OnExit(GameState::SlotSelecting), slot_screen_cleanup_system)
+            .add_systems(
+                Update,
+                (slot_button_system, slot_name_input_system)
+                    .chain()
+                    .run_if(in_state(GameState::SlotSelecting)),
+            );
+    }
+}
+
+#[derive(Component)]
+struct SlotScreen;
+
+#[derive(Clone, Copy, Component)]
+enum SlotButton {
+    Play(SlotId),
+    Rename(SlotId),
+    Copy(SlotId),
+    Delete(SlotId),
+    ConfirmRename,
+    ConfirmDelete(SlotId),
+    CancelEdit,
+    Create,
+    Back,
+}
+
+/// A slot being renamed, or waiting for its deletion to be confirmed.
+#[derive(Default, Resource)]
+enum SlotEdit {
+    #[default]
+    None,
+    Renaming {
+        id: SlotId,
+        name: String,
+    },
+    Deleting(SlotId),
+}
+
+fn spawn_slot_screen(
+    commands: &mut Commands,
+    asset_server: &Res<AssetServer>,
+    save_slots: &SaveSlots,
+    slot_edit: &SlotEdit,
+) {
+    use crate::menu::nodes::*;
+
+    let spawn_button = |parent: &mut ChildBuilder, action: SlotButton, label: &str| {
+        parent
+            .spawn(button())
+            .insert(action)
+            .with_children(|parent| {
+                parent.spawn(button_text(asset_server, label));
+            });
+    };
+    commands
+        .spawn(screen_root())
+        .insert(SlotScreen)
+        .with_children(|parent| {
+            parent.spawn(title_text(asset_server, "Choose a save slot"));
+            for (id, data) in save_slots.list() {
+                parent
+                    .spawn(slot_row())
+                    .with_children(|parent| match slot_edit {
+                        SlotEdit::Renaming { id: renamed, name } if *renamed == id => {
+                            parent.spawn(slot_summary(
+                                asset_server,
+                                &format!("{name}|"),
+                                "Type a new name",
+                            ));
+                            spawn_button(parent, SlotButton::ConfirmRename, "Rename");
+                            spawn_button(parent, SlotButton::CancelEdit, "Cancel");
+                        }
+                        SlotEdit::Deleting(deleted) if *deleted == id => {
+                            parent.spawn(slot_summary(
+                                asset_server,
+                                &data.name,
+                                "Delete this slot for good?",
+                            ));
+                            spawn_button(parent, SlotButton::ConfirmDelete(id), "Delete");
+                            spawn_button(parent, SlotButton::CancelEdit, "Keep");
+                        }
+                        _ => {
+                            parent.spawn(slot_summary(asset_server, &data.name, &data.summary()));
+                            spawn_button(parent, SlotButton::Play(id), "Play");
+                            spawn_button(parent, SlotButton::Rename(id), "Rename");
+                            spawn_button(parent, SlotButton::Copy(id), "Copy");
+                            spawn_button(parent, SlotButton::Delete(id), "Delete");
+                        }
+                    });
+            }
+            parent.spawn(button_row()).with_children(|parent| {
+                spawn_button(parent, SlotButton::Create, "New slot");
+                spawn_button(parent, SlotButton::Back, "Back");
+            });
+        });
+}
+
+fn respawn_slot_screen(
+    commands: &mut Commands,
+    asset_server: &Res<AssetServer>,
+    save_slots: &SaveSlots,
+    slot_edit: &SlotEdit,
+    screens: &Query<Entity, With<SlotScreen>>,
+) {
+    for screen in screens.iter() {
+        commands.entity(screen).despawn_recursive();
+    }
+    spawn_slot_screen(commands, asset_server, save_slots, slot_edit);
+}
+
+fn slot_screen_setup_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    save_slots: Res<SaveSlots>,
+    mut slot_edit: ResMut<SlotEdit>,
+) {
+    *slot_edit = SlotEdit::None;
+    spawn_slot_screen(&mut commands, &asset_server, &save_slots, &slot_edit);
+}
+
+fn slot_screen_cleanup_system(mut commands: Commands, screens: Query<Entity, With<SlotScreen>>) {
+    for screen in screens.iter() {
+        commands.entity(screen).despawn_recursive();
+    }
+}
+
+/// Keeps a renamed slot's name, unless nothing but spaces is left of it.
+fn confirm_rename(save_slots: &SaveSlots, slot_edit: &mut SlotEdit) {
+    if let SlotEdit::Renaming { id, name } = std::mem::take(slot_edit) {
+        if !name.trim().is_empty() {
+            save_slots.rename(id, name.trim());
+        }
+    }
+}
+
+fn slot_button_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    save_slots: Res<SaveSlots>,
+    mut slot_edit: ResMut<SlotEdit>,
+    mut next_state: ResMut<NextState<GameState>>,
+    buttons: Query<(&Interaction, &SlotButton), Changed<Interaction>>,
+    screens: Query<Entity, With<SlotScreen>>,
+) {
+    for (interaction, action) in buttons.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        match *action {
+            SlotButton::Play(id) => {
+                let Some(data) = save_slots.read(id) else {
+                    continue;
+                };
+                load_slot(&mut commands, id, data);
+                next_state.set(GameState::LevelLoading);
+                return;
+            }
+            SlotButton::Rename(id) => {
+                let Some(data) = save_slots.read(id) else {
+                    continue;
+                };
+                *slot_edit = SlotEdit::Renaming {
+                    id,
+                    name: data.name,
+                };
+            }
+            SlotButton::Copy(id) => {
+                save_slots.copy(id);
+            }
+            SlotButton::Delete(id) => *slot_edit = SlotEdit::Deleting(id),
+            SlotButton::ConfirmRename => confirm_rename(&save_slots, &mut slot_edit),
+            SlotButton::ConfirmDelete(id) => {
+                save_slots.delete(id);
+                *slot_edit = SlotEdit::None;
+            }
+            SlotButton::CancelEdit => *slot_edit = SlotEdit::None,
+            SlotButton::Create => {
+                save_slots.create();
+            }
+            SlotButton::Back => {
+                next_state.set(GameState::MainMenu);
+                return;
+            }
+        }
+        respawn_slot_screen(
+            &mut commands,
+            &asset_server,
+            &save_slots,
+            &slot_edit,
+            &screens,
+        );
+        return;
+    }
+}
+
+/// Types into the name of the slot being renamed.
+fn slot_name_input_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    save_slots: Res<SaveSlots>,
+    mut slot_edit: ResMut<SlotEdit>,
+    mut keyboard_input: EventReader<KeyboardInput>,
+    screens: Query<Entity, With<SlotScreen>>,
+) {
+    let SlotEdit::Renaming { name, .. } = &mut *slot_edit else {
+        keyboard_input.clear();
+        return;
+    };
+    let mut changed = false;
+    let mut finished = None;
+    for event in keyboard_input.read() {
+        if !event.state.is_pressed() {
+            continue;
+        }
+        let length = name.chars().count();
+        match &event.logical_key {
+            Key::Character(text) => name.extend(
+                text.chars()
+                    .filter(|character| !character.is_control())
+                    .take(MAX_SLOT_NAME_LENGTH.saturating_sub(length)),
+            ),
+            Key::Space if length < MAX_SLOT_NAME_LENGTH => name.push(' '),
+            Key::Backspace => {
+                name.pop();
+            }
+            Key::Enter | Key::Escape => {
+                finished = Some(event.logical_key == Key::Enter);
+                break;
+            }
+            _ => continue,
+        }
+        changed = true;
+    }
+    match finished {
+        Some(true) => confirm_rename(&save_slots, &mut slot_edit),
+        Some(false) => *slot_edit = SlotEdit::None,
+        None => {}
+    }
+    if changed || finished.is_some() {
+        respawn_slot_screen(
+            &mut commands,
+            &asset_server,
+            &save_slots,
+            &slot_edit,
+            &screens,
+        );
+    }
+}
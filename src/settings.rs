@@ -0,0 +1,372 @@
+use crate::{
+    actions::InputMap,
+    physics::{TickRate, DEFAULT_TICK_RATE},
+};
+use bevy::{
+    audio::Volume,
+    prelude::*,
+    transform::TransformSystem,
+    window::{PresentMode, PrimaryWindow, WindowMode},
+};
+use bevy_rapier2d::prelude::*;
+use controls::ControlsScreenPlugin;
+use screen::SettingsScreenPlugin;
+use serde::{Deserialize, Serialize};
+use std::{fs, path::PathBuf};
+
+mod controls;
+mod screen;
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        let settings_file = SettingsFile::default();
+        app.add_plugins((SettingsScreenPlugin, ControlsScreenPlugin))
+            .insert_resource(settings_file.read())
+            .insert_resource(settings_file)
+            .add_systems(
+                Update,
+                (
+                    apply_settings_system,
+                    playing_sound_volume_system,
+                    save_settings_system,
+                )
+                    .run_if(resource_changed::<Settings>),
+            )
+            // Bevy starts queued sounds after the transforms are propagated.
+            .add_systems(
+                PostUpdate,
+                new_sound_volume_system.before(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+const SETTINGS_DIRECTORY: &str = "puzzle-up";
+const SETTINGS_FILE_NAME: &str = "settings.ron";
+const VOLUME_STEP: f32 = 0.1;
+const TEXT_SIZE_STEP: f32 = 0.1;
+const MIN_TEXT_SIZE: f32 = 0.7;
+const MAX_TEXT_SIZE: f32 = 1.5;
+
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
+enum WindowModeSetting {
+    #[default]
+    Windowed,
+    Borderless,
+    Fullscreen,
+}
+
+impl WindowModeSetting {
+    fn next(self) -> Self {
+        match self {
+            WindowModeSetting::Windowed => WindowModeSetting::Borderless,
+            WindowModeSetting::Borderless => WindowModeSetting::Fullscreen,
+            WindowModeSetting::Fullscreen => WindowModeSetting::Windowed,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            WindowModeSetting::Windowed => "Windowed",
+            WindowModeSetting::Borderless => "Borderless",
+            WindowModeSetting::Fullscreen => "Fullscreen",
+        }
+    }
+}
+
+impl From<WindowModeSetting> for WindowMode {
+    fn from(value: WindowModeSetting) -> Self {
+        match value {
+            WindowModeSetting::Windowed => WindowMode::Windowed,
+            WindowModeSetting::Borderless => {
+                WindowMode::BorderlessFullscreen(MonitorSelection::Current)
+            }
+            WindowModeSetting::Fullscreen => WindowMode::Fullscreen(MonitorSelection::Current),
+        }
+    }
+}
+
+/// The volume setting a sound plays at, on top of the master volume.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Component)]
+pub enum AudioChannel {
+    Music,
+    SoundEffects,
+}
+
+/// Player preferences, read from disk at startup and written back whenever they change.
+#[derive(Clone, Debug, PartialEq, Resource, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    window_mode: WindowModeSetting,
+    vsync: bool,
+    master_volume: f32,
+    music_volume: f32,
+    sfx_volume: f32,
+    text_size: f32,
+    debug_overlay: bool,
+    /// Physics ticks per second; only reachable through the settings file.
+    tick_rate: f64,
+    controls: InputMap,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            window_mode: WindowModeSetting::default(),
+            vsync: true,
+            master_volume: 1.0,
+            music_volume: 1.0,
+            sfx_volume: 1.0,
+            text_size: 1.0,
+            debug_overlay: true,
+            tick_rate: DEFAULT_TICK_RATE,
+            controls: InputMap::default(),
+        }
+    }
+}
+
+impl Settings {
+    fn channel_volume(&self, channel: AudioChannel) -> f32 {
+        match channel {
+            AudioChannel::Music => self.music_volume,
+            AudioChannel::SoundEffects => self.sfx_volume,
+        }
+    }
+}
+
+/// Steps a setting and rounds it, so repeated steps never drift off the grid.
+fn step(value: f32, delta: f32, min: f32, max: f32) -> f32 {
+    ((value + delta) * 10.0)
+        .round()
+        .clamp(min * 10.0, max * 10.0)
+        / 10.0
+}
+
+#[derive(Resource)]
+struct SettingsFile {
+    path: Option<PathBuf>,
+}
+
+impl Default for SettingsFile {
+    fn default() -> Self {
+        Self {
+            path: dirs::config_dir()
+                .map(|dir| dir.join(SETTINGS_DIRECTORY).join(SETTINGS_FILE_NAME)),
+        }
+    }
+}
+
+impl SettingsFile {
+    fn read(&self) -> Settings {
+        let Some(path) = &self.path else {
+            return Settings::default();
+        };
+        let Ok(content) = fs::read_to_string(path) else {
+            return Settings::default();
+        };
+        ron::from_str(&content)
+            .inspect_err(|error| warn!("Ignoring the unreadable settings {path:?}: {error}"))
+            .unwrap_or_default()
+    }
+
+    fn write(&self, settings: &Settings) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let content = ron::ser::to_string_pretty(settings, ron::ser::PrettyConfig::default())
+            .expect("The settings are always serializable!");
+        if let Err(error) = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(path, content))
+        {
+            warn!("Could not write the settings {path:?}: {error}");
+        }
+    }
+}
+
+fn apply_settings_system(
+    settings: Res<Settings>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+    mut global_volume: ResMut<GlobalVolume>,
+    mut ui_scale: ResMut<UiScale>,
+    mut input_map: ResMut<InputMap>,
+    debug_render_context: Option<ResMut<DebugRenderContext>>,
+    tick_rate: Option<ResMut<TickRate>>,
+) {
+    for mut window in windows.iter_mut() {
+        window.mode = settings.window_mode.into();
+        window.present_mode = if settings.vsync {
+            PresentMode::AutoVsync
+        } else {
+            PresentMode::AutoNoVsync
+        };
+    }
+    *global_volume = GlobalVolume::new(settings.master_volume);
+    ui_scale.0 = settings.text_size;
+    *input_map = settings.controls.clone();
+    if let Some(mut debug_render_context) = debug_render_context {
+        debug_render_context.enabled = settings.debug_overlay;
+    }
+    if let Some(mut tick_rate) = tick_rate {
+        tick_rate.set_if_neq(TickRate(settings.tick_rate));
+    }
+}
+
+/// Sets the volume of sounds before they start playing; Bevy adds the master volume.
+fn new_sound_volume_system(
+    settings: Res<Settings>,
+    mut new_sounds: Query<(&AudioChannel, &mut PlaybackSettings), Added<AudioChannel>>,
+) {
+    for (channel, mut playback_settings) in new_sounds.iter_mut() {
+        playback_settings.volume = Volume::new(settings.channel_volume(*channel));
+    }
+}
+
+/// Bevy only applies the global volume to a sound when it starts, so playing ones are set here.
+fn playing_sound_volume_system(
+    settings: Res<Settings>,
+    playing_sounds: Query<(&AudioChannel, &AudioSink)>,
+) {
+    for (channel, sink) in playing_sounds.iter() {
+        sink.set_volume(settings.master_volume * settings.channel_volume(*channel));
+    }
+}
+
+/// The settings read at startup are already on disk, so only later changes get written.
+fn save_settings_system(settings: Res<Settings>, settings_file: Res<SettingsFile>) {
+    if settings.is_added() {
+        return;
+    }
+    settings_file.write(&settings);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_settings_file(name: &str) -> SettingsFile {
+        let directory = std::env::temp_dir()
+            .join(format!("puzzle-up-{}", std::process::id()))
+            .join(name);
+        let _ = fs::remove_dir_all(&directory);
+        SettingsFile {
+            path: Some(directory.join(SETTINGS_FILE_NAME)),
+        }
+    }
+
+    #[test]
+    fn test_changed_settings_are_saved_and_read_back() {
+        // Given
+        let mut app = App::new();
+
+        let settings_file = test_settings_file("settings");
+        app.insert_resource(settings_file.read())
+            .insert_resource(settings_file)
+            .add_systems(
+                Update,
+                save_settings_system.run_if(resource_changed::<Settings>),
+            );
+        app.update();
+        assert!(!app
+            .world()
+            .resource::<SettingsFile>()
+            .path
+            .as_ref()
+            .unwrap()
+            .exists());
+
+        // When
+        let mut settings = app.world_mut().resource_mut::<Settings>();
+        settings.window_mode = WindowModeSetting::Borderless;
+        settings.sfx_volume = step(settings.sfx_volume, -VOLUME_STEP, 0.0, 1.0);
+        app.update();
+
+        // Then
+        let settings = app.world().resource::<SettingsFile>().read();
+        assert_eq!(
+            settings,
+            Settings {
+                window_mode: WindowModeSetting::Borderless,
+                sfx_volume: 0.9,
+                ..default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_apply_settings_system_updates_window_volume_and_ui_scale() {
+        // Given
+        let mut app = App::new();
+
+        app.insert_resource(Settings {
+            window_mode: WindowModeSetting::Fullscreen,
+            vsync: false,
+            master_volume: 0.5,
+            text_size: 1.2,
+            ..default()
+        })
+        .init_resource::<GlobalVolume>()
+        .init_resource::<UiScale>()
+        .init_resource::<InputMap>()
+        .add_systems(Update, apply_settings_system);
+        let window = app
+            .world_mut()
+            .spawn((Window::default(), PrimaryWindow))
+            .id();
+
+        // When
+        app.update();
+
+        // Then
+        let window = app.world().get::<Window>(window).unwrap();
+        assert_eq!(
+            window.mode,
+            WindowMode::Fullscreen(MonitorSelection::Current)
+        );
+        assert_eq!(window.present_mode, PresentMode::AutoNoVsync);
+        assert_eq!(app.world().resource::<GlobalVolume>().volume.get(), 0.5);
+        assert_eq!(app.world().resource::<UiScale>().0, 1.2);
+    }
+
+    #[test]
+    fn test_new_sounds_play_at_their_channel_volume() {
+        // Given
+        let mut app = App::new();
+
+        app.insert_resource(Settings {
+            music_volume: 0.3,
+            sfx_volume: 0.6,
+            ..default()
+        })
+        .add_systems(Update, new_sound_volume_system);
+        let music = app
+            .world_mut()
+            .spawn((AudioChannel::Music, PlaybackSettings::LOOP))
+            .id();
+        let sound_effect = app
+            .world_mut()
+            .spawn((AudioChannel::SoundEffects, PlaybackSettings::ONCE))
+            .id();
+
+        // When
+        app.update();
+
+        // Then
+        let volume = |entity: Entity| app.world().get::<PlaybackSettings>(entity).unwrap().volume;
+        assert_eq!(volume(music).get(), 0.3);
+        assert_eq!(volume(sound_effect).get(), 0.6);
+    }
+
+    #[test]
+    fn test_step_stays_within_bounds() {
+        assert_eq!(step(0.95, VOLUME_STEP, 0.0, 1.0), 1.0);
+        assert_eq!(step(0.1, -VOLUME_STEP, 0.0, 1.0), 0.0);
+        assert_eq!(step(0.0, -VOLUME_STEP, 0.0, 1.0), 0.0);
+        assert_eq!(
+            step(MAX_TEXT_SIZE, TEXT_SIZE_STEP, MIN_TEXT_SIZE, MAX_TEXT_SIZE),
+            MAX_TEXT_SIZE
+        );
+    }
+}
@@ -0,0 +1,302 @@
+use super::Settings;
+use crate::{
+    actions::{Action, ActionState, InputMap},
+    GameState,
+};
+use bevy::prelude::*;
+
+pub struct ControlsScreenPlugin;
+
+impl Plugin for ControlsScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Rebinding>()
+            .add_systems(OnEnter(GameState::Controls), controls_screen_setup_system)
+            .add_systems(OnExit(GameState::Controls), controls_screen_cleanup_system)
+            .add_systems(
+                Update,
+                (
+                    leave_controls_system,
+                    control_button_system,
+                    rebind_key_system,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Controls)),
+            );
+    }
+}
+
+/// The action waiting for its new key or gamepad button, if any.
+#[derive(Default, Resource)]
+struct Rebinding {
+    action: Option<Action>,
+    /// The action whose only key was pressed last, which it cannot give up.
+    refused: Option<Action>,
+}
+
+#[derive(Component)]
+struct ControlsScreen;
+
+#[derive(Clone, Copy, Component)]
+enum ControlButton {
+    Rebind(Action),
+    Reset,
+    Back,
+}
+
+fn input_names(keys: &[KeyCode], buttons: &[GamepadButton]) -> String {
+    let names = |names: Vec<String>| {
+        if names.is_empty() {
+            "Unbound".to_string()
+        } else {
+            names.join(", ")
+        }
+    };
+    format!(
+        "{} / {}",
+        names(Vec::from_iter(keys.iter().map(|key| format!("{key:?}")))),
+        names(Vec::from_iter(
+            buttons.iter().map(|button| format!("{button:?}"))
+        )),
+    )
+}
+
+fn spawn_controls_screen(
+    commands: &mut Commands,
+    asset_server: &Res<AssetServer>,
+    controls: &InputMap,
+    rebinding: &Rebinding,
+) {
+    use crate::menu::nodes::*;
+
+    let spawn_button = |parent: &mut ChildBuilder, action: ControlButton, label: &str| {
+        parent
+            .spawn(button())
+            .insert(action)
+            .with_children(|parent| {
+                parent.spawn(button_text(asset_server, label));
+            });
+    };
+    commands
+        .spawn(screen_root())
+        .insert(ControlsScreen)
+        .with_children(|parent| {
+            parent.spawn(title_text(asset_server, "Controls"));
+            for action in Action::ALL {
+                let keys = if rebinding.action != Some(action) {
+                    input_names(controls.keys(action), controls.gamepad_buttons(action))
+                } else if let Some(refused) = rebinding.refused {
+                    format!("{} needs that key, press another...", refused.label())
+                } else {
+                    "Press a key or a gamepad button, or cancel...".to_string()
+                };
+                parent.spawn(slot_row()).with_children(|parent| {
+                    parent.spawn(slot_summary(asset_server, action.label(), &keys));
+                    spawn_button(parent, ControlButton::Rebind(action), "Rebind");
+                });
+            }
+            parent.spawn(button_row()).with_children(|parent| {
+                spawn_button(parent, ControlButton::Reset, "Reset to defaults");
+                spawn_button(parent, ControlButton::Back, "Back");
+            });
+        });
+}
+
+fn respawn_controls_screen(
+    commands: &mut Commands,
+    asset_server: &Res<AssetServer>,
+    controls: &InputMap,
+    rebinding: &Rebinding,
+    screens: &Query<Entity, With<ControlsScreen>>,
+) {
+    for screen in screens.iter() {
+        commands.entity(screen).despawn_recursive();
+    }
+    spawn_controls_screen(commands, asset_server, controls, rebinding);
+}
+
+fn controls_screen_setup_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    settings: Res<Settings>,
+    mut rebinding: ResMut<Rebinding>,
+) {
+    *rebinding = Rebinding::default();
+    spawn_controls_screen(&mut commands, &asset_server, &settings.controls, &rebinding);
+}
+
+fn controls_screen_cleanup_system(
+    mut commands: Commands,
+    screens: Query<Entity, With<ControlsScreen>>,
+) {
+    for screen in screens.iter() {
+        commands.entity(screen).despawn_recursive();
+    }
+}
+
+fn leave_controls_system(
+    action_state: Res<ActionState>,
+    rebinding: Res<Rebinding>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if rebinding.action.is_none() && action_state.just_pressed(Action::Cancel) {
+        next_state.set(GameState::Settings);
+    }
+}
+
+fn control_button_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut settings: ResMut<Settings>,
+    mut rebinding: ResMut<Rebinding>,
+    mut next_state: ResMut<NextState<GameState>>,
+    buttons: Query<(&Interaction, &ControlButton), Changed<Interaction>>,
+    screens: Query<Entity, With<ControlsScreen>>,
+) {
+    for (interaction, action) in buttons.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        match *action {
+            ControlButton::Rebind(action) => {
+                *rebinding = Rebinding {
+                    action: Some(action),
+                    refused: None,
+                }
+            }
+            ControlButton::Reset => {
+                *rebinding = Rebinding::default();
+                settings.controls = InputMap::default();
+            }
+            ControlButton::Back => {
+                next_state.set(GameState::Settings);
+                return;
+            }
+        }
+        respawn_controls_screen(
+            &mut commands,
+            &asset_server,
+            &settings.controls,
+            &rebinding,
+            &screens,
+        );
+        return;
+    }
+}
+
+fn rebind_key_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    mut settings: ResMut<Settings>,
+    mut rebinding: ResMut<Rebinding>,
+    screens: Query<Entity, With<ControlsScreen>>,
+) {
+    let Some(action) = rebinding.action else {
+        return;
+    };
+    if let Some(key) = keyboard_input.get_just_pressed().next() {
+        if settings.controls.keys(Action::Cancel).contains(key) {
+            *rebinding = Rebinding::default();
+        } else if let Err(refused) = settings.controls.bind(action, *key) {
+            rebinding.refused = Some(refused);
+        } else {
+            *rebinding = Rebinding::default();
+        }
+    } else if let Some(button) = gamepads
+        .iter()
+        .find_map(|gamepad| gamepad.digital().get_just_pressed().next())
+    {
+        if !settings
+            .controls
+            .gamepad_buttons(Action::Cancel)
+            .contains(button)
+        {
+            settings.controls.bind_gamepad_button(action, *button);
+        }
+        *rebinding = Rebinding::default();
+    } else {
+        return;
+    }
+    respawn_controls_screen(
+        &mut commands,
+        &asset_server,
+        &settings.controls,
+        &rebinding,
+        &screens,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rebinding_app(action: Action) -> App {
+        let mut app = App::new();
+
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<Font>()
+            .init_resource::<ButtonInput<KeyCode>>()
+            .init_resource::<Settings>()
+            .insert_resource(Rebinding {
+                action: Some(action),
+                refused: None,
+            })
+            .add_systems(Update, rebind_key_system);
+        app
+    }
+
+    fn press(app: &mut App, key: KeyCode) {
+        let mut input = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+        input.clear();
+        input.press(key);
+        app.update();
+    }
+
+    #[test]
+    fn test_pressing_cancel_aborts_the_rebind() {
+        // Given
+        let mut app = rebinding_app(Action::Jump);
+
+        // When
+        press(&mut app, KeyCode::Escape);
+
+        // Then
+        assert_eq!(app.world().resource::<Rebinding>().action, None);
+        assert_eq!(
+            app.world().resource::<Settings>().controls,
+            InputMap::default()
+        );
+    }
+
+    #[test]
+    fn test_the_only_key_of_another_action_keeps_the_rebind_waiting() {
+        // Given
+        let mut app = rebinding_app(Action::Jump);
+
+        // When
+        press(&mut app, KeyCode::Space);
+
+        // Then
+        let rebinding = app.world().resource::<Rebinding>();
+        assert_eq!(rebinding.action, Some(Action::Jump));
+        assert_eq!(rebinding.refused, Some(Action::Interact));
+        assert_eq!(
+            app.world().resource::<Settings>().controls,
+            InputMap::default()
+        );
+
+        // When
+        press(&mut app, KeyCode::KeyJ);
+
+        // Then
+        assert_eq!(app.world().resource::<Rebinding>().action, None);
+        assert_eq!(
+            app.world()
+                .resource::<Settings>()
+                .controls
+                .keys(Action::Jump),
+            &[KeyCode::KeyJ]
+        );
+    }
+}
@@ -0,0 +1,197 @@
+use super::{step, Settings, MAX_TEXT_SIZE, MIN_TEXT_SIZE, TEXT_SIZE_STEP, VOLUME_STEP};
+use crate::{menu::SettingsReturnState, GameState};
+use bevy::prelude::*;
+
+pub struct SettingsScreenPlugin;
+
+impl Plugin for SettingsScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Settings), settings_screen_setup_system)
+            .add_systems(OnExit(GameState::Settings), settings_screen_cleanup_system)
+            .add_systems(
+                Update,
+                setting_button_system.run_if(in_state(GameState::Settings)),
+            );
+    }
+}
+
+#[derive(Component)]
+struct SettingsScreen;
+
+#[derive(Clone, Copy, Component)]
+enum SettingButton {
+    WindowMode,
+    Vsync,
+    MasterVolume(f32),
+    MusicVolume(f32),
+    SfxVolume(f32),
+    TextSize(f32),
+    DebugOverlay,
+    Controls,
+    Back,
+}
+
+fn on_off(value: bool) -> &'static str {
+    if value {
+        "On"
+    } else {
+        "Off"
+    }
+}
+
+fn percent(value: f32) -> String {
+    format!("{}%", (value * 100.0).round())
+}
+
+fn spawn_settings_screen(
+    commands: &mut Commands,
+    asset_server: &Res<AssetServer>,
+    settings: &Settings,
+) {
+    use crate::menu::nodes::*;
+
+    let spawn_button = |parent: &mut ChildBuilder, action: SettingButton, label: &str| {
+        parent
+            .spawn(button())
+            .insert(action)
+            .with_children(|parent| {
+                parent.spawn(button_text(asset_server, label));
+            });
+    };
+    let spawn_toggle = |parent: &mut ChildBuilder, action: SettingButton, label: &str, value| {
+        parent.spawn(slot_row()).with_children(|parent| {
+            parent.spawn(slot_summary(asset_server, label, value));
+            spawn_button(parent, action, "Change");
+        });
+    };
+    let spawn_slider = |parent: &mut ChildBuilder,
+                        action: fn(f32) -> SettingButton,
+                        label,
+                        value: f32,
+                        step: f32| {
+        parent.spawn(slot_row()).with_children(|parent| {
+            parent.spawn(slot_summary(asset_server, label, &percent(value)));
+            spawn_button(parent, action(-step), "-");
+            spawn_button(parent, action(step), "+");
+        });
+    };
+    commands
+        .spawn(screen_root())
+        .insert(SettingsScreen)
+        .with_children(|parent| {
+            parent.spawn(title_text(asset_server, "Settings"));
+            spawn_toggle(
+                parent,
+                SettingButton::WindowMode,
+                "Window mode",
+                settings.window_mode.label(),
+            );
+            spawn_toggle(
+                parent,
+                SettingButton::Vsync,
+                "VSync",
+                on_off(settings.vsync),
+            );
+            spawn_slider(
+                parent,
+                SettingButton::MasterVolume,
+                "Master volume",
+                settings.master_volume,
+                VOLUME_STEP,
+            );
+            spawn_slider(
+                parent,
+                SettingButton::MusicVolume,
+                "Music volume",
+                settings.music_volume,
+                VOLUME_STEP,
+            );
+            spawn_slider(
+                parent,
+                SettingButton::SfxVolume,
+                "Sound effects volume",
+                settings.sfx_volume,
+                VOLUME_STEP,
+            );
+            spawn_slider(
+                parent,
+                SettingButton::TextSize,
+                "Text size",
+                settings.text_size,
+                TEXT_SIZE_STEP,
+            );
+            spawn_toggle(
+                parent,
+                SettingButton::DebugOverlay,
+                "Physics debug overlay",
+                on_off(settings.debug_overlay),
+            );
+            parent.spawn(button_row()).with_children(|parent| {
+                spawn_button(parent, SettingButton::Controls, "Controls");
+                spawn_button(parent, SettingButton::Back, "Back");
+            });
+        });
+}
+
+fn settings_screen_setup_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    settings: Res<Settings>,
+) {
+    spawn_settings_screen(&mut commands, &asset_server, &settings);
+}
+
+fn settings_screen_cleanup_system(
+    mut commands: Commands,
+    screens: Query<Entity, With<SettingsScreen>>,
+) {
+    for screen in screens.iter() {
+        commands.entity(screen).despawn_recursive();
+    }
+}
+
+fn setting_button_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut settings: ResMut<Settings>,
+    settings_return_state: Res<SettingsReturnState>,
+    mut next_state: ResMut<NextState<GameState>>,
+    buttons: Query<(&Interaction, &SettingButton), Changed<Interaction>>,
+    screens: Query<Entity, With<SettingsScreen>>,
+) {
+    for (interaction, action) in buttons.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        match *action {
+            SettingButton::WindowMode => settings.window_mode = settings.window_mode.next(),
+            SettingButton::Vsync => settings.vsync = !settings.vsync,
+            SettingButton::MasterVolume(delta) => {
+                settings.master_volume = step(settings.master_volume, delta, 0.0, 1.0)
+            }
+            SettingButton::MusicVolume(delta) => {
+                settings.music_volume = step(settings.music_volume, delta, 0.0, 1.0)
+            }
+            SettingButton::SfxVolume(delta) => {
+                settings.sfx_volume = step(settings.sfx_volume, delta, 0.0, 1.0)
+            }
+            SettingButton::TextSize(delta) => {
+                settings.text_size = step(settings.text_size, delta, MIN_TEXT_SIZE, MAX_TEXT_SIZE)
+            }
+            SettingButton::DebugOverlay => settings.debug_overlay = !settings.debug_overlay,
+            SettingButton::Controls => {
+                next_state.set(GameState::Controls);
+                return;
+            }
+            SettingButton::Back => {
+                next_state.set(settings_return_state.0.clone());
+                return;
+            }
+        }
+        for screen in screens.iter() {
+            commands.entity(screen).despawn_recursive();
+        }
+        spawn_settings_screen(&mut commands, &asset_server, &settings);
+        return;
+    }
+}
@@ -0,0 +1,226 @@
+//! Plays the shipped map from the starting level to "You win!" without a window or a GPU.
+//!
+//! The harness boots the game's gameplay plugins on top of a headless renderer and drives them with
+//! keyboard events, the same way a player would: walk to a door, press Space, type the answer
+//! and press Enter, then walk through the opened door.
+
+use bevy::{
+    audio::AudioPlugin,
+    input::{
+        keyboard::{Key, KeyboardInput},
+        ButtonState,
+    },
+    prelude::*,
+    render::{
+        settings::{RenderCreation, WgpuSettings},
+        RenderPlugin,
+    },
+    time::TimeUpdateStrategy,
+    window::ExitCondition,
+    winit::WinitPlugin,
+};
+use bevy_ecs_ldtk::prelude::*;
+use puzzle_up::{
+    map::STARTING_LEVEL, player::Player, riddles::RiddleInfo, GameState, GameplayPlugins,
+};
+use std::time::Duration;
+
+const LEVEL_0: &str = "6c6ef290-5110-11ed-90f2-ab2793fe3460";
+/// The level that congratulates the player.
+const LEVEL_1: &str = "c6297940-7820-11ed-b18f-93bdd3731ebf";
+const LEVEL_2: &str = "6f77e0d0-9f30-11ed-bc5b-a53ea0238275";
+/// Frames allowed for any single step of the script, a minute of game time.
+const STEP_FRAMES: usize = 60 * 60;
+
+struct Playthrough {
+    app: App,
+}
+
+impl Playthrough {
+    fn new() -> Self {
+        let mut app = App::new();
+        app.add_plugins(
+            DefaultPlugins
+                .set(WindowPlugin {
+                    primary_window: None,
+                    exit_condition: ExitCondition::DontExit,
+                    close_when_requested: false,
+                })
+                .set(RenderPlugin {
+                    render_creation: RenderCreation::Automatic(WgpuSettings {
+                        backends: None,
+                        ..default()
+                    }),
+                    ..default()
+                })
+                .disable::<WinitPlugin>()
+                .disable::<AudioPlugin>(),
+        )
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+            1.0 / 60.0,
+        )))
+        .insert_state(GameState::LevelLoading)
+        .add_plugins(GameplayPlugins);
+        app.finish();
+        app.cleanup();
+        Self { app }
+    }
+
+    fn state(&self) -> GameState {
+        self.app
+            .world()
+            .resource::<State<GameState>>()
+            .get()
+            .clone()
+    }
+
+    fn run_until(&mut self, description: &str, condition: impl Fn(&mut Self) -> bool) {
+        for _ in 0..STEP_FRAMES {
+            self.app.update();
+            if condition(self) {
+                return;
+            }
+        }
+        panic!("Timed out waiting until {description}!");
+    }
+
+    fn send_key(&mut self, key_code: KeyCode, logical_key: Key, state: ButtonState) {
+        self.app.world_mut().send_event(KeyboardInput {
+            key_code,
+            logical_key,
+            state,
+            repeat: false,
+            window: Entity::PLACEHOLDER,
+        });
+    }
+
+    fn tap(&mut self, key_code: KeyCode, logical_key: Key) {
+        self.send_key(key_code, logical_key.clone(), ButtonState::Pressed);
+        self.app.update();
+        self.send_key(key_code, logical_key, ButtonState::Released);
+        self.app.update();
+    }
+
+    fn player_x(&mut self) -> f32 {
+        let world = self.app.world_mut();
+        world
+            .query_filtered::<&GlobalTransform, With<Player>>()
+            .single(world)
+            .translation()
+            .x
+    }
+
+    fn door_x(&mut self, next_level: &str) -> f32 {
+        let world = self.app.world_mut();
+        world
+            .query::<(&GlobalTransform, &RiddleInfo)>()
+            .iter(world)
+            .find(|(_, riddle_info)| riddle_info.next_level() == next_level)
+            .unwrap_or_else(|| panic!("No door leads to {next_level}!"))
+            .0
+            .translation()
+            .x
+    }
+
+    fn wait_for_level(&mut self, level: &str) {
+        self.run_until(&format!("level {level} is loaded"), |playthrough| {
+            playthrough.state() == GameState::MapExploring
+                && *playthrough.app.world().resource::<LevelSelection>()
+                    == LevelSelection::iid(level)
+        });
+    }
+
+    /// Runs towards the door, jumping whenever something stops the player.
+    fn walk_to_door(&mut self, next_level: &str) {
+        let door_x = self.door_x(next_level);
+        let mut last_x = self.player_x();
+        let mut held = None;
+        let mut jumping = false;
+        for _ in 0..STEP_FRAMES {
+            let player_x = self.player_x();
+            let distance = door_x - player_x;
+            let direction = if distance.abs() < 8.0 {
+                None
+            } else if distance > 0.0 {
+                Some((KeyCode::ArrowRight, Key::ArrowRight))
+            } else {
+                Some((KeyCode::ArrowLeft, Key::ArrowLeft))
+            };
+            if held != direction {
+                if let Some((key_code, logical_key)) = held {
+                    self.send_key(key_code, logical_key, ButtonState::Released);
+                }
+                if let Some((key_code, logical_key)) = direction.clone() {
+                    self.send_key(key_code, logical_key, ButtonState::Pressed);
+                }
+                held = direction.clone();
+            }
+            let blocked = direction.is_some() && (player_x - last_x).abs() < 0.1;
+            if blocked != jumping {
+                let state = if blocked {
+                    ButtonState::Pressed
+                } else {
+                    ButtonState::Released
+                };
+                self.send_key(KeyCode::ArrowUp, Key::ArrowUp, state);
+                jumping = blocked;
+            }
+            if direction.is_none() && !jumping {
+                self.app.update();
+                return;
+            }
+            last_x = player_x;
+            self.app.update();
+        }
+        panic!("Timed out walking to the door to {next_level}!");
+    }
+
+    fn solve_door(&mut self, next_level: &str, answer: &str) {
+        self.walk_to_door(next_level);
+        self.tap(KeyCode::Space, Key::Space);
+        self.run_until("the riddle opens", |playthrough| {
+            playthrough.state() == GameState::RiddleSolving
+        });
+        for character in answer.chars() {
+            let key_code = match character {
+                '0' => KeyCode::Digit0,
+                '1' => KeyCode::Digit1,
+                '2' => KeyCode::Digit2,
+                '3' => KeyCode::Digit3,
+                '4' => KeyCode::Digit4,
+                '5' => KeyCode::Digit5,
+                '6' => KeyCode::Digit6,
+                '7' => KeyCode::Digit7,
+                '8' => KeyCode::Digit8,
+                '9' => KeyCode::Digit9,
+                _ => panic!("No key for {character:?}"),
+            };
+            self.tap(key_code, Key::Character(character.to_string().into()));
+        }
+        self.tap(KeyCode::Enter, Key::Enter);
+        self.run_until("the riddle is solved", |playthrough| {
+            playthrough.state() == GameState::MapExploring
+        });
+        self.tap(KeyCode::Space, Key::Space);
+        self.wait_for_level(next_level);
+    }
+}
+
+#[test]
+fn test_playthrough_reaches_the_winning_level() {
+    // Given
+    let mut playthrough = Playthrough::new();
+    playthrough.wait_for_level(STARTING_LEVEL);
+
+    // When
+    playthrough.solve_door(LEVEL_0, "702");
+    playthrough.solve_door(LEVEL_2, "177");
+    playthrough.solve_door(LEVEL_1, "174");
+
+    // Then
+    assert_eq!(
+        *playthrough.app.world().resource::<LevelSelection>(),
+        LevelSelection::iid(LEVEL_1)
+    );
+    assert_eq!(playthrough.state(), GameState::MapExploring);
+}